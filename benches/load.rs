@@ -0,0 +1,51 @@
+//! Compares parsing large lists on one thread and on several.
+//!
+//! Run with `cargo bench --features bench`. Both runs parse the same decoded contents, so only the parsing
+//! itself is timed.
+
+use std::{
+    hint::black_box,
+    num::NonZero,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anansi::{List, bench::deserialise_list_on};
+
+const RUNS: u32 = 10;
+
+fn main() {
+    let source = Path::new("test-data/very-large-todo.txt");
+    let contents = std::fs::read_to_string(source).unwrap();
+    let threads = thread::available_parallelism().map_or(1, NonZero::get);
+
+    for (name, contents) in [
+        ("very-large-todo.txt", contents.clone()),
+        ("very-large-todo.txt x10", contents.repeat(10)),
+    ] {
+        let contents = contents.trim();
+        let tasks = deserialise_list_on(source, contents, 1).task_amount();
+        println!("{} ({} tasks)", name, tasks);
+        let sequential = measure(|| deserialise_list_on(source, contents, 1));
+        println!("  1 thread:  {:>8.2} ms", millis(sequential));
+        let parallel = measure(|| deserialise_list_on(source, contents, threads));
+        println!("  {} threads: {:>8.2} ms", threads, millis(parallel));
+    }
+}
+
+/// Returns the fastest of `RUNS` runs of `parse`.
+fn measure<F: FnMut() -> List>(mut parse: F) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(parse());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
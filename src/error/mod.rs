@@ -1,12 +1,18 @@
-use std::fmt;
+use std::{fmt, path::PathBuf};
 
 pub type AnansiResult<T> = Result<T, AnansiError>;
 
 #[derive(Debug)]
 pub enum AnansiError {
     Generic(String),
+    /// The file was changed by someone else; holds the path and the lines that could not be
+    /// reconciled.
+    Conflict(PathBuf, Vec<String>),
     Io(std::io::Error),
     InvalidID(String),
+    InvalidSort(String),
+    /// The lock file at the path is held by someone else.
+    Locked(PathBuf),
     MissingCompletionDate(usize),
 }
 
@@ -14,8 +20,20 @@ impl fmt::Display for AnansiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AnansiError::Generic(msg) => write!(f, "{}", msg),
+            AnansiError::Conflict(path, lines) => {
+                write!(
+                    f,
+                    "{} was changed by someone else; conflicting lines: {:?}",
+                    path.display(),
+                    lines
+                )
+            }
             AnansiError::Io(err) => write!(f, "{}", err),
             AnansiError::InvalidID(msg) => write!(f, "{}", msg),
+            AnansiError::InvalidSort(msg) => write!(f, "{}", msg),
+            AnansiError::Locked(path) => {
+                write!(f, "{} is locked by another process", path.display())
+            }
             AnansiError::MissingCompletionDate(id) => {
                 write!(
                     f,
@@ -31,7 +49,10 @@ impl std::error::Error for AnansiError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             AnansiError::Generic(_)
+            | AnansiError::Conflict(_, _)
             | AnansiError::InvalidID(_)
+            | AnansiError::InvalidSort(_)
+            | AnansiError::Locked(_)
             | AnansiError::MissingCompletionDate(_) => None,
             AnansiError::Io(err) => Some(err),
         }
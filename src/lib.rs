@@ -1,13 +1,30 @@
 #![doc = include_str!("../README.md")]
 mod error;
 mod list;
+mod storage;
 mod task;
 mod util;
+mod workspace;
 
 pub use error::AnansiError;
 pub mod vec {
     pub use crate::list::{search_vec_task_prio, search_vec_task_text, sort_vec_task};
 }
-pub use list::List;
+pub use list::{
+    Archive, BackupPolicy, ChangeSet, Completion, ConflictStrategy, Group, GroupKey, GroupName,
+    List, ListEvent, ReloadPolicy, TransferOptions, WatchedList,
+};
+pub use storage::{DirectoryStorage, FileStorage, MemoryStorage, Storage};
 pub use task::Task;
-pub use util::{Date, SortBy};
+pub use util::{
+    Date, DecodeIssue, Fingerprint, LineEnding, LockOptions, NullsPlacement, SortBy, SortDirection,
+    SortKey, SortSpec, SortTerm,
+};
+pub use workspace::{GlobalId, Workspace};
+
+/// Internals used by the benchmarks, not part of the public API.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::list::deserialise_list_on;
+}
@@ -0,0 +1,45 @@
+use crate::{error::AnansiResult, util::encode};
+
+use super::List;
+
+impl List {
+    /// Appends the tasks added since the file was last loaded or saved to it, if nothing else
+    /// changed, instead of writing the whole list.
+    ///
+    /// Returns `false` if the list has to be written in full.
+    pub(super) fn append_new_tasks(&mut self) -> AnansiResult<bool> {
+        let Some(snapshot) = &self.snapshot else {
+            return Ok(false);
+        };
+        // Replacing invalid UTF-8 means rewriting the lines that hold it
+        if !self.format.issues.is_empty()
+            || !self.supports_append()
+            || !snapshot.has_format(&self.format)
+            || !snapshot.is_appendable(&self.order)
+        {
+            return Ok(false);
+        }
+        let (saved, added) = self.order.split_at(snapshot.len());
+        if saved.iter().any(|id| self.dirty_ids.contains(id)) {
+            return Ok(false);
+        }
+        // The file has to exist, or only the new tasks would be written
+        if self.stored_version()?.is_none() {
+            return Ok(false);
+        }
+
+        let mut text = String::new();
+        for id in added {
+            text.push_str(&self.tasks[id].to_string());
+            text.push('\n');
+        }
+        let raw = encode(&text, false, self.format.line_ending);
+        self.append_stored(&raw)?;
+        let modified = self.stored_modified();
+        let ids = self.order.clone();
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.append(&text, &raw, modified, ids);
+        }
+        Ok(true)
+    }
+}
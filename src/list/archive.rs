@@ -0,0 +1,173 @@
+use std::{ops::Deref, path::PathBuf};
+
+use crate::{
+    AnansiError, Task,
+    error::AnansiResult,
+    util::{FileLock, decode, write_atomic},
+};
+
+use super::{List, TaskID};
+
+/// A done file, loaded read-only.
+///
+/// Dereferences to a `List` of the archived tasks, so all queries work on it. Use `include`
+/// to query archived and current tasks together.
+///
+/// # Example
+/// ```
+/// use anansi::{Archive, List};
+///
+/// let mut list = List::new("archive_struct_doc.txt");
+/// list.add("x Task 1 +garden");
+/// list.add("Task 2 +garden");
+/// list.archive("archive_struct_doc_done.txt").unwrap();
+///
+/// let archive = Archive::load("archive_struct_doc_done.txt").unwrap();
+/// assert_eq!(archive.task_amount(), 1);
+/// assert_eq!(archive.include(&list).by_project("garden").tasks().len(), 2);
+/// # std::fs::remove_file("archive_struct_doc_done.txt").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Archive {
+    list: List,
+}
+
+impl Deref for Archive {
+    type Target = List;
+
+    fn deref(&self) -> &List {
+        &self.list
+    }
+}
+
+impl Archive {
+    /// Load the done file at `path`.
+    ///
+    /// A missing file is an empty archive. Will error if the file exists but cannot be read.
+    pub fn load<P: Into<PathBuf>>(path: P) -> AnansiResult<Archive> {
+        let path = path.into();
+        let list = match List::load(path.clone()) {
+            Ok(list) => list,
+            Err(AnansiError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                List::new_empty_with_path(path)
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(Archive { list })
+    }
+
+    /// Returns a new list with the tasks of `list` followed by the archived tasks.
+    ///
+    /// The tasks get new ids. The list has an empty path and is meant for queries and
+    /// statistics; changes to it do not affect `list` or the done file.
+    pub fn include(&self, list: &List) -> List {
+        let tasks: Vec<Task> = list.ordered().chain(self.list.ordered()).cloned().collect();
+        List::from(tasks)
+    }
+}
+
+impl List {
+    /// Returns the done file that `save` archives done tasks to, if any.
+    pub fn archive_path(&self) -> Option<&PathBuf> {
+        self.archive_path.as_ref()
+    }
+
+    /// Sets the done file that `save` archives done tasks to before saving.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("archive_path_doc.txt");
+    /// list.set_archive_path(Some("archive_path_doc_done.txt"));
+    /// list.add("x Task 1");
+    /// list.add("Task 2");
+    /// list.save().unwrap();
+    ///
+    /// assert_eq!(List::load("archive_path_doc.txt").unwrap().task_amount(), 1);
+    /// assert_eq!(List::load("archive_path_doc_done.txt").unwrap().task_amount(), 1);
+    /// # std::fs::remove_file("archive_path_doc.txt").unwrap();
+    /// # std::fs::remove_file("archive_path_doc_done.txt").unwrap();
+    /// ```
+    pub fn set_archive_path<P: Into<PathBuf>>(&mut self, path: Option<P>) {
+        self.archive_path = path.map(Into::into);
+    }
+
+    /// Move all done tasks to the done file at `done_path`, like `todo.sh archive`.
+    ///
+    /// The tasks are appended to the done file, which is replaced atomically, and then removed
+    /// from the list in one undo step. The list itself is not saved. If locking is enabled,
+    /// the done file is locked while writing it.
+    ///
+    /// Returns the number of archived tasks. Will error if the done file cannot be read or
+    /// written; the list is unchanged in that case.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("archive_doc.txt");
+    /// list.add("x Task 1");
+    /// list.add("Task 2");
+    /// list.add("x Task 3");
+    ///
+    /// assert_eq!(list.archive("archive_doc_done.txt").unwrap(), 2);
+    /// assert_eq!(list.task_amount(), 1);
+    /// assert_eq!(
+    ///     std::fs::read_to_string("archive_doc_done.txt").unwrap(),
+    ///     "x Task 1\nx Task 3\n"
+    /// );
+    /// # std::fs::remove_file("archive_doc_done.txt").unwrap();
+    /// ```
+    pub fn archive<P: Into<PathBuf>>(&mut self, done_path: P) -> AnansiResult<usize> {
+        let done_path = done_path.into();
+        let ids: Vec<TaskID> = self
+            .entries()
+            .filter(|(_, task)| task.is_done())
+            .map(|(id, _)| id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let _lock = match &self.lock_options {
+            Some(options) => Some(FileLock::acquire(&done_path, options)?),
+            None => None,
+        };
+        let mut contents = match std::fs::read(&done_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        // Keep the line endings of the done file as they are
+        let line_ending = decode(&contents)
+            .line_ending
+            .unwrap_or(self.format.line_ending)
+            .as_str();
+        if !contents.is_empty() && !contents.ends_with(b"\n") {
+            contents.extend_from_slice(line_ending.as_bytes());
+        }
+        for task in ids.iter().map(|id| &self.tasks[id]) {
+            contents.extend_from_slice(task.to_string().as_bytes());
+            contents.extend_from_slice(line_ending.as_bytes());
+        }
+        write_atomic(&done_path, &contents)?;
+
+        self.begin_group();
+        for id in &ids {
+            if let Some(change) = self.removal(*id) {
+                self.apply(change);
+            }
+        }
+        self.end_group();
+        Ok(ids.len())
+    }
+
+    /// Archives the done tasks to the archive path, if set.
+    pub(super) fn auto_archive(&mut self) -> AnansiResult<()> {
+        if let Some(done_path) = self.archive_path.clone() {
+            self.archive(done_path)?;
+        }
+        Ok(())
+    }
+}
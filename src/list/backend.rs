@@ -0,0 +1,96 @@
+use std::{io, sync::Arc, time::SystemTime};
+
+use crate::{
+    FileStorage, Storage,
+    error::AnansiResult,
+    util::{file_version, modified, write_atomic},
+};
+
+use super::{List, builder::build_default_list};
+
+impl List {
+    /// Open a list stored in `storage`.
+    ///
+    /// If the storage holds nothing yet, the list is empty. The list is saved back to the same
+    /// storage, and has an empty path. Backups, locking and history persistence work on the
+    /// file at the path of a list, so they are not available for lists opened this way.
+    ///
+    /// Will error if the storage cannot be read. Lines that are not valid UTF-8 are reported by
+    /// `decode_issues`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, MemoryStorage};
+    ///
+    /// let storage = MemoryStorage::with_contents("Task 1\nx Task 2\n");
+    /// let list = List::from_storage(storage).unwrap();
+    /// assert_eq!(list.open_task_amount(), 1);
+    /// assert!(list.storage().is_some());
+    /// ```
+    pub fn from_storage<S: Storage + 'static>(storage: S) -> AnansiResult<List> {
+        let storage: Arc<dyn Storage> = Arc::new(storage);
+        match storage.read() {
+            Ok(contents) => Ok(List::from_stored("", Some(storage), &contents)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let mut list = build_default_list("");
+                list.storage = Some(storage);
+                Ok(list)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Returns the storage the list was opened from, or `None` if it lives in the file at its
+    /// path.
+    pub fn storage(&self) -> Option<&dyn Storage> {
+        self.storage.as_deref()
+    }
+
+    /// Reads the stored list.
+    pub(super) fn read_stored(&self) -> io::Result<Vec<u8>> {
+        match &self.storage {
+            Some(storage) => storage.read(),
+            None => std::fs::read(&self.file_path),
+        }
+    }
+
+    /// Replaces the stored list with `contents`.
+    pub(super) fn write_stored(&self, contents: &[u8]) -> io::Result<()> {
+        match &self.storage {
+            Some(storage) => storage.write(contents),
+            None => write_atomic(&self.file_path, contents),
+        }
+    }
+
+    /// Checks if lines can be appended to the stored list without replacing it.
+    pub(super) fn supports_append(&self) -> bool {
+        self.storage
+            .as_ref()
+            .is_none_or(|storage| storage.supports_append())
+    }
+
+    /// Appends `contents` to the stored list.
+    pub(super) fn append_stored(&self, contents: &[u8]) -> io::Result<()> {
+        match &self.storage {
+            Some(storage) => storage.append(contents),
+            None => FileStorage::new(&self.file_path).append(contents),
+        }
+    }
+
+    /// Returns the size and modification time of the stored list, without reading a file if
+    /// possible. `None` if nothing is stored.
+    pub(super) fn stored_version(&self) -> io::Result<Option<(u64, Option<SystemTime>)>> {
+        match &self.storage {
+            Some(storage) => storage.version(),
+            None => file_version(&self.file_path),
+        }
+    }
+
+    /// Returns the modification time of the stored list, if known.
+    pub(super) fn stored_modified(&self) -> Option<SystemTime> {
+        match &self.storage {
+            Some(_) => self.stored_version().ok().flatten()?.1,
+            None => modified(&self.file_path),
+        }
+    }
+}
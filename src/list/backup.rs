@@ -0,0 +1,230 @@
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    AnansiError,
+    error::AnansiResult,
+    util::{decode, parent_dir},
+};
+
+use super::{List, builder::deserialise_list};
+
+/// Which backups `List::save` keeps of the file it replaces.
+///
+/// Backups are stored next to the list file:
+/// - `Numbered(n)` keeps `todo.txt.bak.1` (newest) up to `todo.txt.bak.n` (oldest)
+/// - `Timestamped(n)` keeps the `n` newest of `todo.txt.bak.2024-01-31T12-00-00.000Z` (UTC)
+///
+/// A count of `0` keeps no backups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackupPolicy {
+    #[default]
+    None,
+    Numbered(usize),
+    Timestamped(usize),
+}
+
+impl List {
+    /// Returns the backup policy used by `save`.
+    pub fn backup_policy(&self) -> BackupPolicy {
+        self.backup_policy
+    }
+
+    /// Sets the backup policy used by `save`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{BackupPolicy, List};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_backup_policy(BackupPolicy::Numbered(3));
+    /// assert_eq!(list.backup_policy(), BackupPolicy::Numbered(3));
+    /// ```
+    pub fn set_backup_policy(&mut self, policy: BackupPolicy) {
+        self.backup_policy = policy;
+    }
+
+    /// Returns the paths of all existing backups of the list file, newest first.
+    ///
+    /// Both numbered and timestamped backups are returned, regardless of the current policy.
+    pub fn backups(&self) -> AnansiResult<Vec<PathBuf>> {
+        let Some(prefix) = backup_prefix(&self.file_path) else {
+            return Ok(Vec::new());
+        };
+        let mut found: Vec<(SystemTime, BackupName, PathBuf)> = Vec::new();
+        for entry in std::fs::read_dir(parent_dir(&self.file_path))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(suffix) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let name = if let Ok(n) = suffix.parse::<usize>() {
+                BackupName::Numbered(n)
+            } else if is_timestamp(suffix) {
+                BackupName::Timestamped(Reverse(suffix.to_string()))
+            } else {
+                continue;
+            };
+            let modified = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .unwrap_or(UNIX_EPOCH);
+            found.push((modified, name, entry.path()));
+        }
+        // Newest first; backups written within the same tick of the clock are ordered by name
+        found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        Ok(found.into_iter().map(|(_, _, path)| path).collect())
+    }
+
+    /// Restore a backup of the list file.
+    ///
+    /// `index` is the position in `backups`, so `0` restores the newest backup.
+    /// The list is replaced by the contents of the backup and saved; the replaced file is backed
+    /// up according to the backup policy like on any other save.
+    ///
+    /// Will error if there is no backup at `index` or the backup cannot be read or saved.
+    /// If saving fails, e.g. on a conflict, the list is left as it was, history included.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{BackupPolicy, List};
+    ///
+    /// let mut list = List::new("restore_backup_doc.txt");
+    /// list.set_backup_policy(BackupPolicy::Numbered(2));
+    /// list.add("Task 1");
+    /// list.save().unwrap();
+    /// list.add("Task 2");
+    /// list.save().unwrap();
+    ///
+    /// list.restore_backup(0).unwrap();
+    /// assert_eq!(list.tasks().len(), 1);
+    /// # for path in list.backups().unwrap() { std::fs::remove_file(path).unwrap(); }
+    /// # std::fs::remove_file("restore_backup_doc.txt").unwrap();
+    /// ```
+    pub fn restore_backup(&mut self, index: usize) -> AnansiResult<()> {
+        let backups = self.backups()?;
+        let Some(backup) = backups.get(index) else {
+            return Err(AnansiError::Generic(format!(
+                "No backup with index {} exists for {}",
+                index,
+                self.file_path.display()
+            )));
+        };
+        let contents = decode(&std::fs::read(backup)?).text;
+        let previous = self.clone();
+        self.adopt(deserialise_list(self.file_path.clone(), contents.trim()));
+        self.save().inspect_err(|_| *self = previous)
+    }
+
+    /// Creates a backup of the list file according to the backup policy.
+    pub(super) fn rotate_backups(&self) -> AnansiResult<()> {
+        if !self.file_path.is_file() {
+            return Ok(());
+        }
+        match self.backup_policy {
+            BackupPolicy::None | BackupPolicy::Numbered(0) | BackupPolicy::Timestamped(0) => {}
+            BackupPolicy::Numbered(keep) => {
+                let oldest = numbered_path(&self.file_path, keep);
+                if oldest.exists() {
+                    std::fs::remove_file(oldest)?;
+                }
+                for n in (1..keep).rev() {
+                    let from = numbered_path(&self.file_path, n);
+                    if from.exists() {
+                        std::fs::rename(from, numbered_path(&self.file_path, n + 1))?;
+                    }
+                }
+                std::fs::copy(&self.file_path, numbered_path(&self.file_path, 1))?;
+            }
+            BackupPolicy::Timestamped(keep) => {
+                let mut path = self.file_path.clone().into_os_string();
+                path.push(format!(".bak.{}", timestamp(SystemTime::now())));
+                std::fs::copy(&self.file_path, path)?;
+
+                let Some(prefix) = backup_prefix(&self.file_path) else {
+                    return Ok(());
+                };
+                let mut existing: Vec<PathBuf> = std::fs::read_dir(parent_dir(&self.file_path))?
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| {
+                        entry
+                            .file_name()
+                            .to_string_lossy()
+                            .strip_prefix(&prefix)
+                            .is_some_and(is_timestamp)
+                    })
+                    .map(|entry| entry.path())
+                    .collect();
+                // Timestamps sort chronologically, so the newest are last
+                existing.sort();
+                let surplus = existing.len().saturating_sub(keep);
+                for path in existing.into_iter().take(surplus) {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum BackupName {
+    Numbered(usize),
+    Timestamped(Reverse<String>),
+}
+
+fn backup_prefix(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| format!("{}.bak.", name.to_string_lossy()))
+}
+
+fn numbered_path(path: &Path, n: usize) -> PathBuf {
+    let mut path = path.to_path_buf().into_os_string();
+    path.push(format!(".bak.{}", n));
+    PathBuf::from(path)
+}
+
+// `YYYY-MM-DDTHH-MM-SS.mmmZ`; `:` is not allowed in file names on every platform.
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+fn is_timestamp(suffix: &str) -> bool {
+    suffix.len() == 24
+        && suffix.ends_with('Z')
+        && suffix.as_bytes()[10] == b'T'
+        && suffix
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | 'T' | '.' | 'Z'))
+}
+
+// Days since 1970-01-01 to a proleptic gregorian date.
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -1,52 +1,215 @@
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead},
+    num::NonZero,
+    panic::resume_unwind,
+    path::PathBuf,
+    thread,
+};
 
-use super::{List, Task, TaskID};
+use crate::util::{LineEndingCounter, decode_line, strip_bom};
+
+use super::{
+    BackupPolicy, ConflictStrategy, EventQueue, History, List, Task, TaskID, TextFormat,
+    index::TagIndex,
+};
 
 pub fn build_default_list<P: Into<PathBuf>>(path: P) -> List {
     List {
         file_path: path.into(),
         tasks: BTreeMap::new(),
+        order: Vec::new(),
         open_tasks: Vec::new(),
         done_tasks: Vec::new(),
         max_id: None,
+        index: TagIndex::default(),
+        backup_policy: BackupPolicy::default(),
+        snapshot: None,
+        conflict_strategy: ConflictStrategy::default(),
+        lock_options: None,
+        history: History::default(),
+        journal: None,
+        events: EventQueue::default(),
+        assign_uids: false,
+        dirty_ids: BTreeSet::new(),
+        storage: None,
+        format: TextFormat::default(),
+        archive_path: None,
+        sort_on_save: None,
     }
 }
 
+/// Lists with at least this many lines are parsed on several threads, if there are any.
+const PARALLEL_THRESHOLD: usize = 16_384;
+
 pub fn deserialise_list<P: Into<PathBuf>, S: AsRef<str>>(path: P, contents: S) -> List {
-    let task_amount = contents.as_ref().lines().count();
-    let mut tasks: BTreeMap<TaskID, Task> = BTreeMap::new();
-    // Probably Overallocates both open and done tasks;
-    // This is a bonus for optimisation when working with large lists (marking as done / creating new tasks == no expansion for some time)
-    //
-    // Example: 1000 tasks; 500 done; 500 open -> Both lists have a capacity of 1000; No expansion
-    // of either list is required until they hit 1000 tasks held
-    let mut open_tasks: Vec<TaskID> = Vec::with_capacity(task_amount);
-    let mut done_tasks: Vec<TaskID> = Vec::with_capacity(task_amount);
-
-    for (id, task) in contents.as_ref().lines().enumerate() {
-        let task = Task::new(task, id);
+    let threads = thread::available_parallelism().map_or(1, NonZero::get);
+    deserialise_list_on(path, contents, threads)
+}
+
+/// Same as `deserialise_list`, but with at most `threads` threads to parse on.
+pub fn deserialise_list_on<P: Into<PathBuf>, S: AsRef<str>>(
+    path: P,
+    contents: S,
+    threads: usize,
+) -> List {
+    let lines: Vec<&str> = contents.as_ref().lines().collect();
+    let mut builder = ListBuilder::with_capacity(lines.len());
+    if lines.len() >= PARALLEL_THRESHOLD && threads > 1 {
+        for task in parse_parallel(&lines, threads) {
+            builder.push_task(task);
+        }
+    } else {
+        for line in lines {
+            builder.push(line);
+        }
+    }
+    builder.finish(path)
+}
+
+/// Parses `lines` into tasks with consecutive ids from 1, splitting them into one chunk per
+/// thread. The tasks are returned in the order of the lines.
+pub fn parse_parallel(lines: &[&str], threads: usize) -> Vec<Task> {
+    let chunk_size = lines.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let chunks: Vec<_> = lines
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(n, chunk)| {
+                let first_id = n * chunk_size + 1;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, line)| Task::new(line, first_id + offset))
+                        .collect::<Vec<Task>>()
+                })
+            })
+            .collect();
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.join().unwrap_or_else(|panic| resume_unwind(panic)))
+            .collect()
+    })
+}
+
+/// Reads a list line by line, with the same result as `deserialise_list` on the trimmed and
+/// decoded contents.
+pub fn read_list<P: Into<PathBuf>, R: BufRead>(path: P, mut reader: R) -> io::Result<List> {
+    let mut builder = ListBuilder::with_capacity(0);
+    let mut format = TextFormat::default();
+    let mut line_endings = LineEndingCounter::default();
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    // The last non-blank line is held back, as it has to be trimmed if it is the last one
+    let mut held: Option<String> = None;
+    let mut blanks: Vec<String> = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        if line_number == 1 {
+            format.bom = strip_bom(&mut line);
+        }
+        let content = line_endings.strip(&line);
+        let content = decode_line(content, line_number, &mut format.issues);
+        if content.trim().is_empty() {
+            if held.is_some() {
+                blanks.push(content.into_owned());
+            }
+            continue;
+        }
+        match held.replace(content.into_owned()) {
+            Some(previous) => {
+                builder.push(&previous);
+                for blank in blanks.drain(..) {
+                    builder.push(&blank);
+                }
+            }
+            None => held = held.map(|first| first.trim_start().to_string()),
+        }
+    }
+    if let Some(last) = held {
+        builder.push(last.trim_end());
+    }
+    format.line_ending = line_endings.detected().unwrap_or_default();
+    let mut list = builder.finish(path);
+    list.format = format;
+    Ok(list)
+}
+
+/// Collects tasks in the order they are read, giving them consecutive ids from 1.
+struct ListBuilder {
+    tasks: BTreeMap<TaskID, Task>,
+    open_tasks: Vec<TaskID>,
+    done_tasks: Vec<TaskID>,
+    index: TagIndex,
+}
+
+impl ListBuilder {
+    fn with_capacity(task_amount: usize) -> ListBuilder {
+        // Probably Overallocates both open and done tasks;
+        // This is a bonus for optimisation when working with large lists (marking as done / creating new tasks == no expansion for some time)
+        //
+        // Example: 1000 tasks; 500 done; 500 open -> Both lists have a capacity of 1000; No expansion
+        // of either list is required until they hit 1000 tasks held
+        ListBuilder {
+            tasks: BTreeMap::new(),
+            open_tasks: Vec::with_capacity(task_amount),
+            done_tasks: Vec::with_capacity(task_amount),
+            index: TagIndex::default(),
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        self.push_task(Task::new(line, self.tasks.len() + 1));
+    }
+
+    /// Adds a task parsed elsewhere, which has to have the next id.
+    fn push_task(&mut self, task: Task) {
+        let id = task.id();
+        debug_assert_eq!(id, self.tasks.len() + 1);
         if task.is_done() {
-            done_tasks.push(id);
+            self.done_tasks.push(id);
         } else {
-            open_tasks.push(id);
+            self.open_tasks.push(id);
         }
-        tasks.insert(id, task);
+        self.index.insert(id, &task);
+        self.tasks.insert(id, task);
     }
 
-    let max_id = tasks.keys().max().copied();
-
-    List {
-        file_path: path.into(),
-        tasks,
-        open_tasks,
-        done_tasks,
-        max_id,
+    fn finish<P: Into<PathBuf>>(self, path: P) -> List {
+        let max_id = self.tasks.keys().max().copied();
+        List {
+            file_path: path.into(),
+            order: self.tasks.keys().copied().collect(),
+            tasks: self.tasks,
+            open_tasks: self.open_tasks,
+            done_tasks: self.done_tasks,
+            max_id,
+            index: self.index,
+            backup_policy: BackupPolicy::default(),
+            snapshot: None,
+            conflict_strategy: ConflictStrategy::default(),
+            lock_options: None,
+            history: History::default(),
+            journal: None,
+            events: EventQueue::default(),
+            assign_uids: false,
+            dirty_ids: BTreeSet::new(),
+            storage: None,
+            format: TextFormat::default(),
+            archive_path: None,
+            sort_on_save: None,
+        }
     }
 }
 
 pub fn serialise_list(list: &List) -> String {
     let mut output = String::new();
-    for (_, task) in list.tasks.iter() {
+    for task in list.ordered() {
         output.push_str(&task.to_string());
         output.push('\n');
     }
@@ -0,0 +1,217 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use crate::{Date, Task};
+
+use super::{List, TaskID, index::normalise_tag};
+
+/// A single candidate returned by `List::complete`.
+///
+/// Replacing the `range` of the input with `text` applies the completion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    text: String,
+    range: Range<usize>,
+    uses: usize,
+    last_used: Date,
+}
+
+impl Completion {
+    /// Returns the text that should replace the partial input, e.g. `+project` or `due:`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Returns the byte range of the input the completion replaces.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+    /// Returns how many tasks use the completed tag.
+    pub fn uses(&self) -> usize {
+        self.uses
+    }
+    /// Returns the most recent inception or completion date of any task using the completed tag.
+    ///
+    /// The date is not set if none of these tasks has a date.
+    pub fn last_used(&self) -> Date {
+        self.last_used
+    }
+}
+
+impl List {
+    /// Complete the tag under the cursor.
+    ///
+    /// `cursor` is a byte offset into `input`; the partial tag is everything between the last
+    /// whitespace before the cursor and the cursor itself.
+    ///
+    /// - `+pro` completes project tags
+    /// - `@ho` completes context tags
+    /// - `du` completes special tag keys, e.g. to `due:`
+    /// - `status:wa` completes values previously used with the special tag key `status`
+    ///
+    /// Matching is case insensitive. Candidates are ranked by the amount of tasks using them,
+    /// then by how recently they were used (judged by inception and completion dates), then
+    /// alphabetically.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 +project +programming status:waiting");
+    /// list.add("2022-01-01 Task 2 +project status:wanted");
+    /// list.add("Task 3 +Programming status:waiting");
+    ///
+    /// let input = "Task 4 +pro";
+    /// let completions = list.complete(input, input.len());
+    /// // Both are used twice, but `+project` was used more recently
+    /// assert_eq!(completions[0].text(), "+project");
+    /// assert_eq!(completions[1].text(), "+programming");
+    ///
+    /// let input = "Task 4 status:wa @home";
+    /// let completions = list.complete(input, 16);
+    /// assert_eq!(completions[0].text(), "status:waiting");
+    /// assert_eq!(completions[0].range(), 7..16);
+    /// ```
+    pub fn complete(&self, input: &str, cursor: usize) -> Vec<Completion> {
+        let mut cursor = cursor.min(input.len());
+        while !input.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        let start = input[..cursor]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(idx, c)| idx + c.len_utf8())
+            .unwrap_or(0);
+        let token = &input[start..cursor];
+        let range = start..cursor;
+
+        let mut completions = if let Some(partial) = token.strip_prefix('+') {
+            self.complete_tags(self.index.projects(), partial, Task::projects, "+")
+        } else if let Some(partial) = token.strip_prefix('@') {
+            self.complete_tags(self.index.contexts(), partial, Task::contexts, "@")
+        } else if let Some((key, partial)) = token.split_once(':') {
+            self.complete_special_values(key, partial)
+        } else if !token.is_empty() {
+            self.complete_special_keys(token)
+        } else {
+            Vec::new()
+        };
+
+        for completion in completions.iter_mut() {
+            completion.range = range.clone();
+        }
+        completions.sort_by(|a, b| {
+            b.uses
+                .cmp(&a.uses)
+                .then(b.last_used.cmp(&a.last_used))
+                .then(a.text.cmp(&b.text))
+        });
+        completions
+    }
+
+    fn complete_tags(
+        &self,
+        index: &BTreeMap<String, BTreeSet<TaskID>>,
+        partial: &str,
+        tags_of: fn(&Task) -> &Vec<String>,
+        prefix: &str,
+    ) -> Vec<Completion> {
+        let partial = normalise_tag(partial);
+        let mut out = Vec::new();
+        for (tag, ids) in index.range(partial.clone()..) {
+            if !tag.starts_with(&partial) {
+                break;
+            }
+            // Offer the tag the way it was written in the first task using it
+            let spelling = self
+                .first_in_list_order(ids)
+                .and_then(|id| {
+                    tags_of(&self.tasks[&id])
+                        .iter()
+                        .find(|t| normalise_tag(t) == *tag)
+                })
+                .unwrap_or(tag);
+            out.push(Completion {
+                text: format!("{}{}", prefix, spelling),
+                range: 0..0,
+                uses: ids.len(),
+                last_used: self.last_used(ids.iter()),
+            });
+        }
+        out
+    }
+
+    fn complete_special_keys(&self, partial: &str) -> Vec<Completion> {
+        let partial = normalise_tag(partial);
+        let mut out = Vec::new();
+        for (key, ids) in self.index.specials().range(partial.clone()..) {
+            if !key.starts_with(&partial) {
+                break;
+            }
+            let spelling = self
+                .first_in_list_order(ids)
+                .and_then(|id| {
+                    self.tasks[&id]
+                        .specials()
+                        .keys()
+                        .find(|k| normalise_tag(k) == *key)
+                })
+                .unwrap_or(key);
+            out.push(Completion {
+                text: format!("{}:", spelling),
+                range: 0..0,
+                uses: ids.len(),
+                last_used: self.last_used(ids.iter()),
+            });
+        }
+        out
+    }
+
+    fn complete_special_values(&self, key: &str, partial: &str) -> Vec<Completion> {
+        let Some(ids) = self.index.specials().get(&normalise_tag(key)) else {
+            return Vec::new();
+        };
+        let partial = partial.to_lowercase();
+        // normalised value -> (spelling, ids)
+        let mut values: BTreeMap<String, (&str, Vec<TaskID>)> = BTreeMap::new();
+        for id in ids {
+            for (task_key, value) in self.tasks[id].specials() {
+                let normalised = value.to_lowercase();
+                if normalise_tag(task_key) == normalise_tag(key) && normalised.starts_with(&partial)
+                {
+                    values
+                        .entry(normalised)
+                        .or_insert_with(|| (value.as_str(), Vec::new()))
+                        .1
+                        .push(*id);
+                }
+            }
+        }
+        values
+            .into_values()
+            .map(|(spelling, ids)| Completion {
+                text: format!("{}:{}", key, spelling),
+                range: 0..0,
+                uses: ids.len(),
+                last_used: self.last_used(ids.iter()),
+            })
+            .collect()
+    }
+
+    fn last_used<'a, I: Iterator<Item = &'a TaskID>>(&self, ids: I) -> Date {
+        let mut last = Date::default();
+        for id in ids {
+            let task = &self.tasks[id];
+            for date in [task.inception_date(), task.completion_date()] {
+                let date = Date::from(date);
+                if date > last {
+                    last = date;
+                }
+            }
+        }
+        last
+    }
+}
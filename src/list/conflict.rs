@@ -0,0 +1,420 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    ops::Range,
+    time::SystemTime,
+};
+
+use crate::{AnansiError, Fingerprint, LineEnding, Task, error::AnansiResult, util::decode};
+
+use super::{List, TaskID, TextFormat, builder::deserialise_list, renumber::remapped};
+
+/// What `List::save` does if the file was changed by someone else since it was loaded or saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Do not save and return `AnansiError::Conflict` with the lines that changed on disk.
+    #[default]
+    Fail,
+    /// Reload the file and reapply the changes made to the list on top of it.
+    /// If a task was changed in both, the change made to the list wins.
+    Reload,
+    /// Merge both versions, using the file as it was loaded as the common base.
+    /// If a task was changed in both, nothing is saved and `AnansiError::Conflict` is returned
+    /// with the conflicting lines of the list.
+    Merge,
+}
+
+/// The file as last seen by the list, used as the base when merging.
+#[derive(Debug, Clone)]
+pub(super) struct Snapshot {
+    fingerprint: Fingerprint,
+    contents: String,
+    // The part of `contents` that was parsed; line `n` belongs to `ids[n]`
+    body: Range<usize>,
+    ids: Vec<TaskID>,
+    // The format the file was read or written in
+    line_ending: LineEnding,
+    bom: bool,
+}
+
+impl Snapshot {
+    /// Checks if a list in `order` only adds tasks to the end of the snapshot, and the file
+    /// ends with a line break so lines can be appended to it.
+    pub(super) fn is_appendable(&self, order: &[TaskID]) -> bool {
+        let ends_with_line = match &self.contents[self.body.end..] {
+            "" => self.contents[self.body.clone()].ends_with('\n'),
+            rest => rest == "\n" || rest == "\r\n",
+        };
+        ends_with_line && order.len() > self.ids.len() && order.starts_with(&self.ids)
+    }
+
+    /// Checks if the file was read or written in `format`, so lines in it can be appended.
+    pub(super) fn has_format(&self, format: &TextFormat) -> bool {
+        self.line_ending == format.line_ending && self.bom == format.bom
+    }
+
+    /// Returns the amount of lines in the snapshot.
+    pub(super) fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Remembers that `text`, encoded as `raw`, was appended to the file, which now holds the
+    /// tasks with `ids`.
+    pub(super) fn append(
+        &mut self,
+        text: &str,
+        raw: &[u8],
+        modified: Option<SystemTime>,
+        ids: Vec<TaskID>,
+    ) {
+        self.contents.truncate(self.body.end);
+        if !self.contents.ends_with('\n') {
+            self.contents.push('\n');
+        }
+        self.contents.push_str(text);
+        self.body.end = self.contents.len();
+        self.fingerprint = self.fingerprint.extended(raw, modified);
+        self.ids = ids;
+    }
+
+    /// Replaces the ids of the lines by their new ids in `ids`.
+    pub(super) fn remap(&mut self, ids: &mut BTreeMap<TaskID, TaskID>) {
+        for id in &mut self.ids {
+            *id = remapped(ids, *id);
+        }
+    }
+
+    fn lines(&self) -> impl Iterator<Item = (TaskID, &str)> {
+        self.ids
+            .iter()
+            .copied()
+            .zip(self.contents[self.body.clone()].lines())
+    }
+}
+
+impl List {
+    /// Returns the strategy used by `save` if the file was changed by someone else.
+    pub fn conflict_strategy(&self) -> ConflictStrategy {
+        self.conflict_strategy
+    }
+
+    /// Sets the strategy used by `save` if the file was changed by someone else.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{ConflictStrategy, List};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_conflict_strategy(ConflictStrategy::Merge);
+    /// assert_eq!(list.conflict_strategy(), ConflictStrategy::Merge);
+    /// ```
+    pub fn set_conflict_strategy(&mut self, strategy: ConflictStrategy) {
+        self.conflict_strategy = strategy;
+    }
+
+    /// Returns the fingerprint of the file as it was last loaded or saved by this list.
+    ///
+    /// `None` if the list was not loaded from or saved to its file yet.
+    pub fn fingerprint(&self) -> Option<&Fingerprint> {
+        self.snapshot.as_ref().map(|snapshot| &snapshot.fingerprint)
+    }
+
+    /// Checks if the file was changed by someone else since it was last loaded or saved.
+    ///
+    /// A list that was never loaded or saved, or whose file does not exist, has no external
+    /// changes.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("has_external_changes_doc.txt");
+    /// list.add("Task 1");
+    /// list.save().unwrap();
+    /// assert!(!list.has_external_changes().unwrap());
+    ///
+    /// std::fs::write("has_external_changes_doc.txt", "Task 1\nTask 2\n").unwrap();
+    /// assert!(list.has_external_changes().unwrap());
+    /// # std::fs::remove_file("has_external_changes_doc.txt").unwrap();
+    /// ```
+    pub fn has_external_changes(&self) -> AnansiResult<bool> {
+        Ok(self.external_contents()?.is_some())
+    }
+
+    /// Remembers `raw` as the current contents of the file, decoded to `contents`, of which
+    /// `body` was parsed into the tasks of the list.
+    pub(super) fn observe(&mut self, raw: &[u8], contents: String, body: Range<usize>) {
+        self.snapshot = Some(Snapshot {
+            fingerprint: Fingerprint::new(raw, self.stored_modified()),
+            contents,
+            body,
+            ids: self.order.clone(),
+            line_ending: self.format.line_ending,
+            bom: self.format.bom,
+        });
+    }
+
+    /// Reconciles the list with external changes according to the conflict strategy.
+    ///
+    /// Returns `true` if the file was changed and the list now holds the reconciled tasks.
+    pub(super) fn reconcile(&mut self) -> AnansiResult<bool> {
+        let Some(theirs) = self.external_contents()? else {
+            return Ok(false);
+        };
+        let theirs: Vec<String> = theirs.trim().lines().map(normalise_line).collect();
+        let merged = match self.conflict_strategy {
+            ConflictStrategy::Fail => {
+                return Err(AnansiError::Conflict(
+                    self.file_path.clone(),
+                    self.external_lines(theirs),
+                ));
+            }
+            ConflictStrategy::Reload => self.merge(theirs, true)?,
+            ConflictStrategy::Merge => self.merge(theirs, false)?,
+        };
+        let path = self.file_path.clone();
+        self.adopt(deserialise_list(path, merged.join("\n").trim()));
+        Ok(true)
+    }
+
+    /// Returns the contents of the file if it differs from the snapshot.
+    fn external_contents(&self) -> AnansiResult<Option<String>> {
+        let Some(snapshot) = &self.snapshot else {
+            return Ok(None);
+        };
+        let current = match self.read_stored() {
+            Ok(current) => current,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if Fingerprint::new(&current, None).same_contents(&snapshot.fingerprint) {
+            Ok(None)
+        } else {
+            Ok(Some(decode(&current).text))
+        }
+    }
+
+    /// Returns the tasks as they were when the file was last loaded or saved.
+    pub(super) fn snapshot_tasks(&self) -> Vec<Task> {
+        self.snapshot
+            .iter()
+            .flat_map(|snapshot| snapshot.lines())
+            .map(|(id, line)| Task::new(normalise_line(line), id))
+            .collect()
+    }
+
+    /// Checks if the list was changed since the file was last loaded or saved.
+    pub(super) fn has_unsaved_changes(&self) -> bool {
+        if self.snapshot.is_none() {
+            return !self.tasks.is_empty();
+        }
+        let base = self.base_lines();
+        base.len() != self.tasks.len()
+            || base
+                .iter()
+                .zip(self.entries())
+                .any(|((base_id, line), (id, task))| *base_id != id || *line != task.to_string())
+    }
+
+    fn base_lines(&self) -> Vec<(TaskID, String)> {
+        self.snapshot
+            .iter()
+            .flat_map(|snapshot| snapshot.lines())
+            .map(|(id, line)| (id, normalise_line(line)))
+            .collect()
+    }
+
+    /// Lines of `theirs` that are not part of the snapshot.
+    fn external_lines(&self, theirs: Vec<String>) -> Vec<String> {
+        let mut base: BTreeMap<String, usize> = BTreeMap::new();
+        for (_, line) in self.base_lines() {
+            *base.entry(line).or_default() += 1;
+        }
+        theirs
+            .into_iter()
+            .filter(|line| match base.get_mut(line) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Applies the changes made to the list since the snapshot on top of `theirs`.
+    fn merge(&self, theirs: Vec<String>, ours_wins: bool) -> AnansiResult<Vec<String>> {
+        let base = self.base_lines();
+        let base_lines: Vec<&str> = base.iter().map(|(_, line)| line.as_str()).collect();
+        let counterparts = align(&base_lines, &theirs);
+
+        let mut merged: Vec<Option<String>> = theirs.iter().cloned().map(Some).collect();
+        let mut inserted: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        let mut conflicts = Vec::new();
+        for ((id, base_line), counterpart) in base.iter().zip(counterparts) {
+            let ours = self.tasks.get(id).map(|task| task.to_string());
+            if ours.as_ref() == Some(base_line) {
+                continue;
+            }
+            match (counterpart, ours) {
+                (Counterpart::Same(n), ours) => merged[n] = ours,
+                // Changed the same way in both
+                (Counterpart::Changed(n), Some(ours)) if ours == theirs[n] => {}
+                (Counterpart::Changed(n), ours) if ours_wins => merged[n] = ours,
+                (Counterpart::Changed(n), ours) => {
+                    conflicts.push(ours.unwrap_or_else(|| theirs[n].clone()))
+                }
+                (Counterpart::Removed(_), None) => {}
+                (Counterpart::Removed(n), Some(ours)) if ours_wins => {
+                    inserted.entry(n).or_default().push(ours)
+                }
+                (Counterpart::Removed(_), Some(ours)) => conflicts.push(ours),
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(AnansiError::Conflict(self.file_path.clone(), conflicts));
+        }
+
+        let mut lines = Vec::with_capacity(merged.len());
+        for (n, line) in merged.into_iter().enumerate() {
+            lines.extend(inserted.remove(&n).unwrap_or_default());
+            lines.extend(line);
+        }
+        lines.extend(inserted.into_values().flatten());
+        let base_ids: BTreeSet<TaskID> = base.iter().map(|(id, _)| *id).collect();
+        lines.extend(
+            self.entries()
+                .filter(|(id, _)| !base_ids.contains(id))
+                .map(|(_, task)| task.to_string()),
+        );
+        Ok(lines)
+    }
+}
+
+/// Where a line of the base ended up in the other version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Counterpart {
+    /// Unchanged at the given line.
+    Same(usize),
+    /// Replaced by the given line.
+    Changed(usize),
+    /// Removed; the given line is where it would be.
+    Removed(usize),
+}
+
+// Lines of the base and the other version are aligned by their longest common subsequence.
+// Above this many comparisons, unchanged lines are only matched by their text.
+const ALIGN_LIMIT: usize = 1 << 22;
+
+/// Finds the counterpart in `theirs` of every line of `base`.
+pub(super) fn align(base: &[&str], theirs: &[String]) -> Vec<Counterpart> {
+    let prefix = base
+        .iter()
+        .zip(theirs)
+        .take_while(|(a, b)| **a == b.as_str())
+        .count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(theirs[prefix..].iter().rev())
+        .take_while(|(a, b)| **a == b.as_str())
+        .count();
+    let base_middle = &base[prefix..base.len() - suffix];
+    let theirs_middle = &theirs[prefix..theirs.len() - suffix];
+
+    let mut counterparts: Vec<Counterpart> = (0..prefix).map(Counterpart::Same).collect();
+    let matches = if base_middle.len() * theirs_middle.len() <= ALIGN_LIMIT {
+        common_subsequence(base_middle, theirs_middle)
+    } else {
+        matching_lines(base_middle, theirs_middle)
+    };
+    // Unmatched lines between two matches are paired up in order
+    let (mut next_base, mut next_theirs) = (0, 0);
+    for (matched_base, matched_theirs) in matches
+        .into_iter()
+        .chain([(base_middle.len(), theirs_middle.len())])
+    {
+        for n in next_base..matched_base {
+            let theirs_n = next_theirs + (n - next_base);
+            counterparts.push(if theirs_n < matched_theirs {
+                Counterpart::Changed(prefix + theirs_n)
+            } else {
+                Counterpart::Removed(prefix + matched_theirs)
+            });
+        }
+        if matched_base < base_middle.len() {
+            counterparts.push(Counterpart::Same(prefix + matched_theirs));
+        }
+        next_base = matched_base + 1;
+        next_theirs = matched_theirs + 1;
+    }
+    let suffix_start = theirs.len() - suffix;
+    counterparts.extend((suffix_start..theirs.len()).map(Counterpart::Same));
+    counterparts
+}
+
+/// Index pairs of the longest common subsequence of `base` and `theirs`.
+fn common_subsequence(base: &[&str], theirs: &[String]) -> Vec<(usize, usize)> {
+    let width = theirs.len() + 1;
+    // lengths[i * width + j] is the length of the LCS of base[i..] and theirs[j..]
+    let mut lengths = vec![0u32; (base.len() + 1) * width];
+    for i in (0..base.len()).rev() {
+        for j in (0..theirs.len()).rev() {
+            lengths[i * width + j] = if base[i] == theirs[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < base.len() && j < theirs.len() {
+        if base[i] == theirs[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+/// Index pairs of equal lines, matched in order of appearance and keeping both orders.
+fn matching_lines(base: &[&str], theirs: &[String]) -> Vec<(usize, usize)> {
+    let mut positions: BTreeMap<&str, VecDeque<usize>> = BTreeMap::new();
+    for (n, line) in theirs.iter().enumerate() {
+        positions.entry(line.as_str()).or_default().push_back(n);
+    }
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    for (n, line) in base.iter().enumerate() {
+        let last = matches.last().map(|(_, last)| *last);
+        if let Some(found) = positions.get_mut(line) {
+            // Only matches after the previous match keep the alignment monotonic
+            while found.front().is_some_and(|front| Some(*front) <= last) {
+                found.pop_front();
+            }
+            if let Some(position) = found.pop_front() {
+                matches.push((n, position));
+            }
+        }
+    }
+    matches
+}
+
+/// Returns the range of `contents` that is left after trimming, as parsed by `List::load`.
+pub(super) fn trimmed_range(contents: &str) -> Range<usize> {
+    let start = contents.len() - contents.trim_start().len();
+    start..start + contents.trim().len()
+}
+
+// Lines are compared in the form the list would write them
+fn normalise_line(line: &str) -> String {
+    if line.trim().is_empty() {
+        String::new()
+    } else {
+        Task::new(line, 0).to_string()
+    }
+}
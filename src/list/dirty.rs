@@ -0,0 +1,59 @@
+use crate::error::AnansiResult;
+
+use super::{List, TaskID};
+
+impl List {
+    /// Returns `true` if the tasks were changed since the list was loaded or last saved.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("is_dirty_doc.txt");
+    /// assert!(!list.is_dirty());
+    /// let id = list.add("Task 1");
+    /// assert!(list.is_dirty());
+    /// assert!(list.is_task_dirty(id));
+    ///
+    /// list.save().unwrap();
+    /// assert!(!list.is_dirty());
+    /// # std::fs::remove_file("is_dirty_doc.txt").unwrap();
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_ids.is_empty()
+    }
+
+    /// Returns `true` if the task with the given id was added, changed or removed since the
+    /// list was loaded or last saved.
+    pub fn is_task_dirty(&self, id: TaskID) -> bool {
+        self.dirty_ids.contains(&id)
+    }
+
+    /// Returns the ids of all tasks added, changed or removed since the list was loaded or last
+    /// saved, in ascending order.
+    pub fn changed_ids(&self) -> Vec<TaskID> {
+        self.dirty_ids.iter().copied().collect()
+    }
+
+    /// Save the list, but only if it is dirty.
+    ///
+    /// Returns `true` if the list was saved.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("save_if_dirty_doc.txt");
+    /// list.add("Task 1");
+    /// assert!(list.save_if_dirty().unwrap());
+    /// assert!(!list.save_if_dirty().unwrap());
+    /// # std::fs::remove_file("save_if_dirty_doc.txt").unwrap();
+    /// ```
+    pub fn save_if_dirty(&mut self) -> AnansiResult<bool> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use crate::Task;
+
+use super::{Change, List, TaskID};
+
+/// A change to a list, as returned by `List::drain_events`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListEvent {
+    /// A task was added, including by undoing its removal.
+    TaskAdded { id: TaskID, task: Task },
+    /// A task was changed.
+    TaskUpdated {
+        id: TaskID,
+        before: Task,
+        after: Task,
+    },
+    /// A task was removed, including by undoing its addition.
+    TaskRemoved { id: TaskID, task: Task },
+    /// A task was moved from one position in the list order to another.
+    TaskMoved { id: TaskID, from: usize, to: usize },
+    /// All tasks were replaced by the contents of the file, e.g. by `reload` or a merging
+    /// `save`. Previously reported ids are no longer valid.
+    Reloaded,
+    /// The tasks were given new ids by `renumber`, mapped from their old ids.
+    Renumbered { ids: BTreeMap<TaskID, TaskID> },
+    /// The list was saved to its file.
+    Saved,
+}
+
+impl From<Change> for ListEvent {
+    fn from(change: Change) -> Self {
+        match change {
+            Change::Insert { id, task, .. } => ListEvent::TaskAdded { id, task },
+            Change::Remove { id, task, .. } => ListEvent::TaskRemoved { id, task },
+            Change::Update { id, before, after } => ListEvent::TaskUpdated { id, before, after },
+            Change::Move { id, from, to } => ListEvent::TaskMoved { id, from, to },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct EventQueue {
+    enabled: bool,
+    events: Vec<ListEvent>,
+    // Total amount of drained events, to tell if events were seen
+    drained: usize,
+}
+
+/// The state of the queue at the start of a transaction.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct EventMark {
+    len: usize,
+    drained: usize,
+}
+
+impl EventQueue {
+    pub(super) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(super) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(super) fn push(&mut self, event: ListEvent) {
+        if self.enabled {
+            self.events.push(event);
+        }
+    }
+
+    pub(super) fn mark(&self) -> EventMark {
+        EventMark {
+            len: self.events.len(),
+            drained: self.drained,
+        }
+    }
+
+    /// Forgets the events since `mark`. Returns `false` if some of them were already drained.
+    pub(super) fn forget_since(&mut self, mark: EventMark) -> bool {
+        if self.drained != mark.drained {
+            return false;
+        }
+        self.events.truncate(mark.len);
+        true
+    }
+}
+
+impl List {
+    /// Returns `true` if the list collects events.
+    pub fn emits_events(&self) -> bool {
+        self.events.enabled
+    }
+
+    /// Sets if the list collects events for `drain_events`.
+    ///
+    /// Disabling it drops all collected events.
+    pub fn set_emit_events(&mut self, emit: bool) {
+        self.events.enabled = emit;
+        if !emit {
+            self.events.events.clear();
+        }
+    }
+
+    /// Returns all events collected since the last call, oldest first.
+    ///
+    /// Every change to a task, whether by `add`, `push_task`, `update_task`, `remove`, `undo`
+    /// or `redo`, creates one event. Changes made by a transaction that fails are dropped
+    /// again, unless they were already drained; in that case the events reverting them follow.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, ListEvent, Task};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_emit_events(true);
+    /// let id = list.add("Task 1");
+    /// list.update_task(Task::new("x Task 1", id), id).unwrap();
+    ///
+    /// let events = list.drain_events();
+    /// assert!(matches!(&events[0], ListEvent::TaskAdded { id: added, .. } if *added == id));
+    /// assert!(matches!(&events[1], ListEvent::TaskUpdated { after, .. } if after.is_done()));
+    /// assert!(list.drain_events().is_empty());
+    /// ```
+    pub fn drain_events(&mut self) -> Vec<ListEvent> {
+        let events = std::mem::take(&mut self.events.events);
+        self.events.drained += events.len();
+        events
+    }
+}
@@ -0,0 +1,107 @@
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    DecodeIssue, LineEnding, Storage,
+    util::{Decoded, decode, encode},
+};
+
+use super::{List, builder::deserialise_list, conflict::trimmed_range};
+
+/// How the file of a list is encoded.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TextFormat {
+    pub(super) line_ending: LineEnding,
+    pub(super) bom: bool,
+    // Lines of the file as last loaded that were not valid UTF-8
+    pub(super) issues: Vec<DecodeIssue>,
+}
+
+impl TextFormat {
+    /// Takes over the format of a file that was read; the line ending is kept if the file has
+    /// no line breaks.
+    pub(super) fn update(&mut self, decoded: &mut Decoded) {
+        if let Some(line_ending) = decoded.line_ending {
+            self.line_ending = line_ending;
+        }
+        self.bom = decoded.bom;
+        self.issues = std::mem::take(&mut decoded.issues);
+    }
+}
+
+impl List {
+    /// Returns the line ending used when saving.
+    ///
+    /// Defaults to the line ending used by most lines of the file when it was loaded, or `\n`
+    /// for new files.
+    pub fn line_ending(&self) -> LineEnding {
+        self.format.line_ending
+    }
+
+    /// Sets the line ending used when saving.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{LineEnding, List};
+    ///
+    /// let mut list = List::from_reader("Task 1\r\nTask 2\r\n".as_bytes()).unwrap();
+    /// assert_eq!(list.line_ending(), LineEnding::CrLf);
+    ///
+    /// list.set_line_ending(LineEnding::Lf);
+    /// let mut output = Vec::new();
+    /// list.write_to(&mut output).unwrap();
+    /// assert_eq!(output, b"Task 1\nTask 2\n");
+    /// ```
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.format.line_ending = line_ending;
+    }
+
+    /// Returns `true` if the list is saved with a UTF-8 byte order mark.
+    ///
+    /// Defaults to whether the file had one when it was loaded.
+    pub fn has_bom(&self) -> bool {
+        self.format.bom
+    }
+
+    /// Sets whether the list is saved with a UTF-8 byte order mark.
+    pub fn set_bom(&mut self, bom: bool) {
+        self.format.bom = bom;
+    }
+
+    /// Returns the lines of the file that were not valid UTF-8 when the list was last loaded.
+    ///
+    /// Invalid bytes are replaced by `U+FFFD` instead of failing to load the whole file.
+    /// Saving the list writes the replaced text, and clears the issues.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let list = List::from_reader(&b"Task 1\nTask \xFF2\n"[..]).unwrap();
+    /// assert_eq!(list.decode_issues()[0].line(), 2);
+    /// assert_eq!(list.decode_issues()[0].bytes(), b"Task \xFF2");
+    /// assert_eq!(list.get(2).unwrap().text(), "Task \u{FFFD}2");
+    /// ```
+    pub fn decode_issues(&self) -> &[DecodeIssue] {
+        &self.format.issues
+    }
+
+    /// Creates a list from `raw`, the contents of its file or storage.
+    pub(super) fn from_stored<P: Into<PathBuf>>(
+        path: P,
+        storage: Option<Arc<dyn Storage>>,
+        raw: &[u8],
+    ) -> List {
+        let mut decoded = decode(raw);
+        let mut list = deserialise_list(path, decoded.text.trim());
+        list.storage = storage;
+        list.format.update(&mut decoded);
+        let body = trimmed_range(&decoded.text);
+        list.observe(raw, decoded.text, body);
+        list
+    }
+
+    /// Encodes the serialised list for writing, according to the format of the list.
+    pub(super) fn encode(&self, serialised: &str) -> Vec<u8> {
+        encode(serialised, self.format.bom, self.format.line_ending)
+    }
+}
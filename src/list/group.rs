@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+use crate::{Date, Task};
+
+use super::{List, TaskID, index::normalise_tag};
+
+/// A property tasks can be grouped by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupKey {
+    /// Project tags, normalised to lowercase. A task with several projects is in several groups.
+    Project,
+    /// Context tags, normalised to lowercase. A task with several contexts is in several groups.
+    Context,
+    /// Priority, e.g. `A`.
+    Priority,
+    /// Month of the completion date, formatted as `YYYY-MM`.
+    CompletionMonth,
+    /// Month of the inception date, formatted as `YYYY-MM`.
+    InceptionMonth,
+    /// Done state, either `open` or `done`.
+    Done,
+    /// Value of the special tag with the given key, normalised to lowercase. The key is
+    /// matched case insensitively.
+    Special(String),
+}
+
+/// The name of a group.
+///
+/// Tasks without a value for the `GroupKey` are collected in the `None` group, which is
+/// ordered after all other groups.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GroupName {
+    Value(String),
+    None,
+}
+
+/// A group of tasks, as returned by `List::group_by`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Group {
+    ids: Vec<TaskID>,
+    open: usize,
+    done: usize,
+    subgroups: BTreeMap<GroupName, Group>,
+}
+
+impl Group {
+    /// Returns the ids of all tasks in the group, in list order.
+    pub fn ids(&self) -> &[TaskID] {
+        &self.ids
+    }
+    /// Returns the amount of tasks in the group.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+    /// Returns `true` if the group contains no tasks.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+    /// Returns the amount of open tasks in the group.
+    pub fn open(&self) -> usize {
+        self.open
+    }
+    /// Returns the amount of done tasks in the group.
+    pub fn done(&self) -> usize {
+        self.done
+    }
+    /// Returns the nested groups created by `List::group_by_nested`.
+    ///
+    /// Empty for the last level of grouping.
+    pub fn subgroups(&self) -> &BTreeMap<GroupName, Group> {
+        &self.subgroups
+    }
+    /// Returns references to all tasks in the group.
+    ///
+    /// `list` has to be the list the group was created from.
+    pub fn tasks<'a>(&self, list: &'a List) -> Vec<&'a Task> {
+        self.ids.iter().filter_map(|id| list.get(*id)).collect()
+    }
+}
+
+impl List {
+    /// Group tasks by the given key.
+    ///
+    /// Returns an ordered map of group name to group. Tasks lacking the key are put into the
+    /// `GroupName::None` group, which is always last.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, GroupKey, GroupName};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 +garden");
+    /// list.add("x Task 2 +garden +House");
+    /// list.add("Task 3");
+    ///
+    /// let groups = list.group_by(GroupKey::Project);
+    /// let names: Vec<&GroupName> = groups.keys().collect();
+    /// assert_eq!(names, vec![
+    ///     &GroupName::Value("garden".to_string()),
+    ///     &GroupName::Value("house".to_string()),
+    ///     &GroupName::None,
+    /// ]);
+    /// let garden = &groups[&GroupName::Value("garden".to_string())];
+    /// assert_eq!(garden.len(), 2);
+    /// assert_eq!(garden.open(), 1);
+    /// assert_eq!(garden.done(), 1);
+    /// ```
+    pub fn group_by(&self, key: GroupKey) -> BTreeMap<GroupName, Group> {
+        self.group_by_nested(&[key])
+    }
+
+    /// Group tasks by several keys, each level nested in the groups of the previous one.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, GroupKey, GroupName};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("(A) Task 1 +garden");
+    /// list.add("(B) Task 2 +garden");
+    /// list.add("(A) Task 3 +house");
+    ///
+    /// let groups = list.group_by_nested(&[GroupKey::Project, GroupKey::Priority]);
+    /// let garden = &groups[&GroupName::Value("garden".to_string())];
+    /// assert_eq!(garden.len(), 2);
+    /// assert_eq!(garden.subgroups().len(), 2);
+    /// let garden_a = &garden.subgroups()[&GroupName::Value("A".to_string())];
+    /// assert_eq!(garden_a.tasks(&list)[0].text(), "Task 1 +garden");
+    /// ```
+    pub fn group_by_nested(&self, keys: &[GroupKey]) -> BTreeMap<GroupName, Group> {
+        let tasks: Vec<&Task> = self.ordered().collect();
+        group_tasks(&tasks, keys)
+    }
+}
+
+fn group_tasks(tasks: &[&Task], keys: &[GroupKey]) -> BTreeMap<GroupName, Group> {
+    let Some((key, rest)) = keys.split_first() else {
+        return BTreeMap::new();
+    };
+    let mut members: BTreeMap<GroupName, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        for name in group_names(task, key) {
+            members.entry(name).or_default().push(task);
+        }
+    }
+    members
+        .into_iter()
+        .map(|(name, tasks)| {
+            let done = tasks.iter().filter(|task| task.is_done()).count();
+            let group = Group {
+                ids: tasks.iter().map(|task| task.id()).collect(),
+                open: tasks.len() - done,
+                done,
+                subgroups: group_tasks(&tasks, rest),
+            };
+            (name, group)
+        })
+        .collect()
+}
+
+fn month(date: String) -> Option<String> {
+    let date = Date::from(date);
+    if date.is_set() {
+        // `YYYY-MM-DD` -> `YYYY-MM`
+        Some(date.format_date()[..7].to_string())
+    } else {
+        None
+    }
+}
+
+fn tag_names<'a>(tags: impl Iterator<Item = &'a str>) -> Vec<GroupName> {
+    tags.map(|tag| GroupName::Value(normalise_tag(tag)))
+        .collect()
+}
+
+pub(crate) fn group_names(task: &Task, key: &GroupKey) -> Vec<GroupName> {
+    let mut names: Vec<GroupName> = match key {
+        GroupKey::Project => tag_names(task.iter_projects()),
+        GroupKey::Context => tag_names(task.iter_contexts()),
+        GroupKey::Priority => task
+            .prio()
+            .map(|prio| GroupName::Value(prio.to_string()))
+            .into_iter()
+            .collect(),
+        GroupKey::CompletionMonth => month(task.completion_date())
+            .map(GroupName::Value)
+            .into_iter()
+            .collect(),
+        GroupKey::InceptionMonth => month(task.inception_date())
+            .map(GroupName::Value)
+            .into_iter()
+            .collect(),
+        GroupKey::Done => {
+            let state = if task.is_done() { "done" } else { "open" };
+            vec![GroupName::Value(state.to_string())]
+        }
+        GroupKey::Special(key) => task
+            .specials()
+            .iter()
+            .filter(|(k, _)| normalise_tag(k) == normalise_tag(key))
+            .map(|(_, value)| GroupName::Value(normalise_tag(value)))
+            .collect(),
+    };
+    if names.is_empty() {
+        return vec![GroupName::None];
+    }
+    // A task tagged `+garden +Garden` is only in the group once
+    names.sort();
+    names.dedup();
+    names
+}
@@ -0,0 +1,503 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+};
+
+use crate::{AnansiError, Task, error::AnansiResult, util::write_atomic};
+
+use super::{List, TaskID, renumber::remapped};
+
+const HISTORY_HEADER: &str = "anansi-history 2";
+
+/// A single change to the tasks of a list, holding everything needed to revert it.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Change {
+    // `position` is the place of the task in the order of the list
+    Insert {
+        id: TaskID,
+        task: Task,
+        position: usize,
+    },
+    Remove {
+        id: TaskID,
+        task: Task,
+        position: usize,
+    },
+    Update {
+        id: TaskID,
+        before: Task,
+        after: Task,
+    },
+    Move {
+        id: TaskID,
+        from: usize,
+        to: usize,
+    },
+}
+
+impl Change {
+    pub(super) fn inverse(self) -> Change {
+        match self {
+            Change::Insert { id, task, position } => Change::Remove { id, task, position },
+            Change::Remove { id, task, position } => Change::Insert { id, task, position },
+            Change::Update { id, before, after } => Change::Update {
+                id,
+                before: after,
+                after: before,
+            },
+            Change::Move { id, from, to } => Change::Move {
+                id,
+                from: to,
+                to: from,
+            },
+        }
+    }
+
+    fn id(&self) -> TaskID {
+        match self {
+            Change::Insert { id, .. }
+            | Change::Remove { id, .. }
+            | Change::Update { id, .. }
+            | Change::Move { id, .. } => *id,
+        }
+    }
+
+    fn with_id(self, id: TaskID) -> Change {
+        match self {
+            Change::Insert { task, position, .. } => Change::Insert {
+                id,
+                task: task.with_id(id),
+                position,
+            },
+            Change::Remove { task, position, .. } => Change::Remove {
+                id,
+                task: task.with_id(id),
+                position,
+            },
+            Change::Update { before, after, .. } => Change::Update {
+                id,
+                before: before.with_id(id),
+                after: after.with_id(id),
+            },
+            Change::Move { from, to, .. } => Change::Move { id, from, to },
+        }
+    }
+}
+
+/// Undo and redo stacks of a list. Each step is a group of changes undone together.
+#[derive(Debug, Clone, Default)]
+pub(super) struct History {
+    depth: usize,
+    persist: bool,
+    undo: VecDeque<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    group: Vec<Change>,
+    open_groups: usize,
+}
+
+impl History {
+    fn is_recording(&self) -> bool {
+        self.depth > 0
+    }
+
+    fn record(&mut self, change: Change) {
+        if self.open_groups > 0 {
+            self.group.push(change);
+        } else {
+            self.push_step(vec![change]);
+        }
+    }
+
+    fn push_step(&mut self, step: Vec<Change>) {
+        if step.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(step);
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+        self.redo.truncate(self.depth);
+    }
+
+    fn close_groups(&mut self) {
+        self.open_groups = 0;
+        let group = std::mem::take(&mut self.group);
+        self.push_step(group);
+    }
+
+    /// Replaces the ids of all changes by their new ids in `ids`.
+    pub(super) fn remap(&mut self, ids: &mut BTreeMap<TaskID, TaskID>) {
+        let steps = self.undo.iter_mut().chain(&mut self.redo);
+        for change in steps.flatten().chain(&mut self.group) {
+            let id = remapped(ids, change.id());
+            *change = change.clone().with_id(id);
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group.clear();
+    }
+}
+
+impl List {
+    /// Applies `change` and records it in the history.
+    pub(super) fn apply(&mut self, change: Change) {
+        if self.history.is_recording() {
+            self.apply_entry(change.clone());
+            self.history.record(change);
+        } else {
+            self.apply_entry(change);
+        }
+    }
+
+    /// Applies `change` without recording it in the history.
+    pub(super) fn apply_entry(&mut self, change: Change) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(change.clone());
+        }
+        if self.events.is_enabled() {
+            self.events.push(change.clone().into());
+        }
+        self.dirty_ids.insert(change.id());
+        match change {
+            Change::Insert { id, task, position } => self.insert_entry(id, task, position),
+            Change::Remove { id, .. } => {
+                self.take_entry(id);
+            }
+            Change::Update { id, after, .. } => {
+                self.replace_entry(id, after);
+            }
+            Change::Move { from, to, .. } => self.move_entry(from, to),
+        }
+    }
+
+    /// Returns the maximum amount of steps that can be undone.
+    ///
+    /// `0`, the default, disables the history.
+    pub fn history_depth(&self) -> usize {
+        self.history.depth
+    }
+
+    /// Sets the maximum amount of steps that can be undone. `0` disables the history.
+    ///
+    /// Every change made by `add`, `push_task`, `update_task` and `remove` is one step, unless
+    /// it is grouped with others using `begin_group` and `end_group`.
+    /// Loading and reloading the list are not recorded, and clear the history.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_history_depth(100);
+    /// let id = list.add("Task 1");
+    /// list.remove(id);
+    /// assert_eq!(list.task_amount(), 0);
+    ///
+    /// assert!(list.undo());
+    /// assert_eq!(list.get(id).unwrap().text(), "Task 1");
+    /// assert!(list.undo());
+    /// assert_eq!(list.task_amount(), 0);
+    /// assert!(!list.undo());
+    ///
+    /// assert!(list.redo());
+    /// assert_eq!(list.task_amount(), 1);
+    /// ```
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.depth = depth;
+        self.history.truncate();
+    }
+
+    /// Returns `true` if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty() || !self.history.group.is_empty()
+    }
+
+    /// Returns `true` if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Reverts the last step. Returns `false` if there was nothing to undo.
+    ///
+    /// Closes all open groups first.
+    pub fn undo(&mut self) -> bool {
+        self.history.close_groups();
+        let Some(step) = self.history.undo.pop_back() else {
+            return false;
+        };
+        for change in step.iter().rev() {
+            self.apply_entry(change.clone().inverse());
+        }
+        self.history.redo.push(step);
+        true
+    }
+
+    /// Repeats the last undone step. Returns `false` if there was nothing to redo.
+    ///
+    /// Any change made after undoing clears the steps to redo.
+    pub fn redo(&mut self) -> bool {
+        self.history.close_groups();
+        let Some(step) = self.history.redo.pop() else {
+            return false;
+        };
+        for change in &step {
+            self.apply_entry(change.clone());
+        }
+        self.history.undo.push_back(step);
+        true
+    }
+
+    /// Starts a group: all changes until the matching `end_group` are undone as one step.
+    ///
+    /// Groups can be nested; only the outermost group forms a step.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_history_depth(10);
+    /// list.begin_group();
+    /// list.add("Task 1");
+    /// list.add("Task 2");
+    /// list.end_group();
+    ///
+    /// list.undo();
+    /// assert_eq!(list.task_amount(), 0);
+    /// ```
+    pub fn begin_group(&mut self) {
+        self.history.open_groups += 1;
+    }
+
+    /// Ends the group started by the last `begin_group`.
+    pub fn end_group(&mut self) {
+        match self.history.open_groups {
+            0 => {}
+            1 => self.history.close_groups(),
+            _ => self.history.open_groups -= 1,
+        }
+    }
+
+    /// Removes all steps from the history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Returns `true` if `save` writes the history next to the list file.
+    pub fn persist_history(&self) -> bool {
+        self.history.persist
+    }
+
+    /// Sets if `save` writes the history next to the list file, named like the list with
+    /// `.history` appended.
+    ///
+    /// After a restart, `load_history` restores it.
+    pub fn set_persist_history(&mut self, persist: bool) {
+        self.history.persist = persist;
+    }
+
+    /// Restores the history written by the last `save` of this file.
+    ///
+    /// Call directly after loading the list, and after setting the history depth; only as many
+    /// steps as the depth allows are restored.
+    ///
+    /// Returns `false` if there is no history file, it was written for a different version
+    /// of the list file, or the list was opened from a `Storage`. Will error if the history file cannot be read or is malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("load_history_doc.txt");
+    /// list.set_history_depth(10);
+    /// list.set_persist_history(true);
+    /// list.add("Task 1");
+    /// list.add("Task 2");
+    /// list.save().unwrap();
+    ///
+    /// let mut list = List::load("load_history_doc.txt").unwrap();
+    /// list.set_history_depth(10);
+    /// assert!(list.load_history().unwrap());
+    /// list.undo();
+    /// assert_eq!(list.task_amount(), 1);
+    /// # std::fs::remove_file("load_history_doc.txt").unwrap();
+    /// # std::fs::remove_file("load_history_doc.txt.history").unwrap();
+    /// ```
+    pub fn load_history(&mut self) -> AnansiResult<bool> {
+        if self.storage.is_some() {
+            return Ok(false);
+        }
+        let contents = match std::fs::read_to_string(self.history_path()) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+        let mut lines = contents.lines();
+        let expected = self
+            .fingerprint()
+            .map(|fingerprint| format!("{} {:016x}", HISTORY_HEADER, fingerprint.hash()));
+        if expected.is_none() || lines.next() != expected.as_deref() {
+            return Ok(false);
+        }
+        let mut undo: Vec<Vec<Change>> = Vec::new();
+        let mut redo: Vec<Vec<Change>> = Vec::new();
+        let mut in_redo = None;
+        for line in lines {
+            match line {
+                "undo" => {
+                    undo.push(Vec::new());
+                    in_redo = Some(false);
+                }
+                "redo" => {
+                    redo.push(Vec::new());
+                    in_redo = Some(true);
+                }
+                line => {
+                    let stack = match in_redo {
+                        Some(true) => &mut redo,
+                        Some(false) => &mut undo,
+                        None => return Err(invalid_history(line)),
+                    };
+                    if let Some(step) = stack.last_mut() {
+                        step.push(parse_change(line)?);
+                    }
+                }
+            }
+        }
+        let max_id = undo.iter().chain(&redo).flatten().map(Change::id).max();
+        if let Some(max_id) = max_id {
+            self.max_id = Some(self.max_id.map_or(max_id, |max| max.max(max_id)));
+        }
+        self.history.clear();
+        self.history.undo = undo.into();
+        self.history.redo = redo;
+        self.history.truncate();
+        Ok(true)
+    }
+
+    fn history_path(&self) -> PathBuf {
+        let mut path = self.file_path.clone().into_os_string();
+        path.push(".history");
+        PathBuf::from(path)
+    }
+
+    /// Writes the history if it is persisted; called after the list was saved.
+    pub(super) fn save_history(&self) -> AnansiResult<()> {
+        if !self.history.persist || self.storage.is_some() {
+            return Ok(());
+        }
+        let Some(fingerprint) = self.fingerprint() else {
+            return Ok(());
+        };
+        // Ids as they will be after loading the saved file; ids of tasks that only exist in
+        // the history come after them
+        let mut ids = self.compacted_ids();
+        let mut output = format!("{} {:016x}\n", HISTORY_HEADER, fingerprint.hash());
+        let undo = self.history.undo.iter().map(|step| ("undo", step));
+        let redo = self.history.redo.iter().map(|step| ("redo", step));
+        for (stack, step) in undo.chain(redo) {
+            output.push_str(stack);
+            output.push('\n');
+            for change in step {
+                let id = remapped(&mut ids, change.id());
+                output.push_str(&format_change(&change.clone().with_id(id)));
+                output.push('\n');
+            }
+        }
+        write_atomic(self.history_path(), output.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn format_change(change: &Change) -> String {
+    match change {
+        Change::Insert { id, task, position } => {
+            format!("+\t{}\t{}\t{}", id, position, escape(task))
+        }
+        Change::Remove { id, task, position } => {
+            format!("-\t{}\t{}\t{}", id, position, escape(task))
+        }
+        Change::Update { id, before, after } => {
+            format!("~\t{}\t{}\t{}", id, escape(before), escape(after))
+        }
+        Change::Move { id, from, to } => format!(">\t{}\t{}\t{}", id, from, to),
+    }
+}
+
+fn parse_change(line: &str) -> AnansiResult<Change> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let id = fields
+        .get(1)
+        .and_then(|id| id.parse::<TaskID>().ok())
+        .ok_or_else(|| invalid_history(line))?;
+    let task = |n: usize| -> AnansiResult<Task> {
+        let text = fields.get(n).ok_or_else(|| invalid_history(line))?;
+        Ok(Task::new(unescape(text), id))
+    };
+    let position = |n: usize| -> AnansiResult<usize> {
+        fields
+            .get(n)
+            .and_then(|position| position.parse().ok())
+            .ok_or_else(|| invalid_history(line))
+    };
+    match (fields[0], fields.len()) {
+        ("+", 4) => Ok(Change::Insert {
+            id,
+            position: position(2)?,
+            task: task(3)?,
+        }),
+        ("-", 4) => Ok(Change::Remove {
+            id,
+            position: position(2)?,
+            task: task(3)?,
+        }),
+        ("~", 4) => Ok(Change::Update {
+            id,
+            before: task(2)?,
+            after: task(3)?,
+        }),
+        (">", 4) => Ok(Change::Move {
+            id,
+            from: position(2)?,
+            to: position(3)?,
+        }),
+        _ => Err(invalid_history(line)),
+    }
+}
+
+fn invalid_history(line: &str) -> AnansiError {
+    AnansiError::Generic(format!("Invalid history entry: {}", line))
+}
+
+fn escape(task: &Task) -> String {
+    task.to_string()
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => output.push('\t'),
+            Some('n') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
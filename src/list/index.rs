@@ -0,0 +1,131 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use crate::Task;
+
+use super::TaskID;
+
+/// Inverted indexes from a normalised (lowercase) tag to the ids of all tasks carrying it.
+///
+/// Kept up to date by every method of `List` that inserts, replaces or removes a task.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct TagIndex {
+    contexts: BTreeMap<String, BTreeSet<TaskID>>,
+    projects: BTreeMap<String, BTreeSet<TaskID>>,
+    specials: BTreeMap<String, BTreeSet<TaskID>>,
+    // Stable ids, as given; not normalised
+    uids: BTreeMap<String, TaskID>,
+}
+
+impl TagIndex {
+    pub fn insert(&mut self, id: TaskID, task: &Task) {
+        for tag in task.iter_contexts() {
+            insert_tag(&mut self.contexts, tag, id);
+        }
+        for tag in task.iter_projects() {
+            insert_tag(&mut self.projects, tag, id);
+        }
+        for (key, _) in task.iter_specials() {
+            insert_tag(&mut self.specials, key, id);
+        }
+        if let Some(uid) = task.uid() {
+            self.uids.insert(uid.to_string(), id);
+        }
+    }
+
+    pub fn remove(&mut self, id: TaskID, task: &Task) {
+        for tag in task.iter_contexts() {
+            remove_tag(&mut self.contexts, tag, id);
+        }
+        for tag in task.iter_projects() {
+            remove_tag(&mut self.projects, tag, id);
+        }
+        for (key, _) in task.iter_specials() {
+            remove_tag(&mut self.specials, key, id);
+        }
+        if let Some(uid) = task.uid()
+            && self.uids.get(uid) == Some(&id)
+        {
+            self.uids.remove(uid);
+        }
+    }
+
+    pub fn contexts(&self) -> &BTreeMap<String, BTreeSet<TaskID>> {
+        &self.contexts
+    }
+
+    pub fn projects(&self) -> &BTreeMap<String, BTreeSet<TaskID>> {
+        &self.projects
+    }
+
+    pub fn specials(&self) -> &BTreeMap<String, BTreeSet<TaskID>> {
+        &self.specials
+    }
+
+    pub fn uid(&self, uid: &str) -> Option<TaskID> {
+        self.uids.get(uid).copied()
+    }
+}
+
+/// Returns the ids of all tasks with a tag containing `predicate`.
+///
+/// `predicate` has to be normalised already.
+pub fn ids_containing(
+    map: &BTreeMap<String, BTreeSet<TaskID>>,
+    predicate: &str,
+) -> BTreeSet<TaskID> {
+    let mut ids = BTreeSet::new();
+    for (tag, tag_ids) in map {
+        if tag.contains(predicate) {
+            ids.extend(tag_ids);
+        }
+    }
+    ids
+}
+
+/// Returns the ids of all tasks with exactly the tag `tag`.
+pub fn ids_matching(map: &BTreeMap<String, BTreeSet<TaskID>>, tag: &str) -> BTreeSet<TaskID> {
+    map.get(&normalise_tag(tag)).cloned().unwrap_or_default()
+}
+
+/// Returns every tag in the map with the amount of tasks carrying it.
+pub fn tag_counts(map: &BTreeMap<String, BTreeSet<TaskID>>) -> Vec<(&str, usize)> {
+    map.iter()
+        .map(|(tag, ids)| (tag.as_str(), ids.len()))
+        .collect()
+}
+
+pub fn normalise_tag<S: AsRef<str>>(tag: S) -> String {
+    tag.as_ref().to_lowercase()
+}
+
+// Loading a large list inserts every tag of every task; most tags are lowercase already, so
+// only allocate when a new tag is seen or the tag actually needs lowercasing.
+fn normalised(tag: &str) -> Cow<'_, str> {
+    if tag.chars().any(char::is_uppercase) {
+        Cow::Owned(normalise_tag(tag))
+    } else {
+        Cow::Borrowed(tag)
+    }
+}
+
+fn insert_tag(map: &mut BTreeMap<String, BTreeSet<TaskID>>, tag: &str, id: TaskID) {
+    let tag = normalised(tag);
+    if let Some(ids) = map.get_mut(tag.as_ref()) {
+        ids.insert(id);
+    } else {
+        map.insert(tag.into_owned(), BTreeSet::from([id]));
+    }
+}
+
+fn remove_tag(map: &mut BTreeMap<String, BTreeSet<TaskID>>, tag: &str, id: TaskID) {
+    let tag = normalised(tag);
+    if let Some(ids) = map.get_mut(tag.as_ref()) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            map.remove(tag.as_ref());
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::{BufRead, BufWriter, Write};
+
+use crate::{error::AnansiResult, util::BOM};
+
+use super::{List, builder::read_list};
+
+impl List {
+    /// Read a list from `reader`, one task per line.
+    ///
+    /// The input is read line by line, without holding all of it in memory. Leading and
+    /// trailing whitespace is ignored, and the line ending and byte order mark are detected,
+    /// like on `load`. The list has an empty path; use `update_path` before saving it.
+    ///
+    /// Will error if reading fails. Lines that are not valid UTF-8 are reported by
+    /// `decode_issues`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let input = "Task 1\nx Task 2\n".as_bytes();
+    /// let list = List::from_reader(input).unwrap();
+    /// assert_eq!(list.open_task_amount(), 1);
+    /// assert_eq!(list.done_task_amount(), 1);
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> AnansiResult<List> {
+        Ok(read_list("", reader)?)
+    }
+
+    /// Write the tasks of the list to `writer`, one task per line, as `save` writes them.
+    ///
+    /// Uses the line ending and byte order mark of the list.
+    ///
+    /// Does not touch the file of the list. Will error if writing fails.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let list = List::from_reader("\n  Task 1\r\nTask 2  \n\n".as_bytes()).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// list.write_to(&mut output).unwrap();
+    /// assert_eq!(output, b"Task 1\nTask 2\n");
+    /// ```
+    pub fn write_to<W: Write>(&self, writer: W) -> AnansiResult<()> {
+        let mut writer = BufWriter::new(writer);
+        if self.format.bom {
+            writer.write_all(BOM.as_bytes())?;
+        }
+        let line_ending = self.format.line_ending.as_str();
+        for task in self.ordered() {
+            write!(writer, "{}{}", task, line_ending)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
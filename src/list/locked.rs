@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use crate::{
+    AnansiError,
+    error::AnansiResult,
+    util::{FileLock, LockOptions},
+};
+
+use super::List;
+
+impl List {
+    /// Returns the options of the lock taken by `save`, if locking is enabled.
+    pub fn lock_options(&self) -> Option<LockOptions> {
+        self.lock_options
+    }
+
+    /// Enables or disables locking the list file while saving.
+    ///
+    /// Locking is advisory: it only keeps out writers that lock the file as well.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, LockOptions};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// assert_eq!(list.lock_options(), None);
+    /// list.set_lock_options(Some(LockOptions::new()));
+    /// assert!(list.lock_options().is_some());
+    /// ```
+    pub fn set_lock_options(&mut self, options: Option<LockOptions>) {
+        self.lock_options = options;
+    }
+
+    /// Load a list from a file while holding its lock.
+    ///
+    /// The returned list locks the file on every `save` as well.
+    ///
+    /// Will error if the lock cannot be taken before the timeout, or the file cannot be read
+    /// or does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, LockOptions};
+    ///
+    /// std::fs::write("load_locked_doc.txt", "Task 1\n").unwrap();
+    /// let mut list = List::load_locked("load_locked_doc.txt", LockOptions::new()).unwrap();
+    /// list.add("Task 2");
+    /// list.save().unwrap();
+    /// # std::fs::remove_file("load_locked_doc.txt").unwrap();
+    /// ```
+    pub fn load_locked<P: Into<PathBuf>>(path: P, options: LockOptions) -> AnansiResult<List> {
+        let path = path.into();
+        let _lock = FileLock::acquire(&path, &options)?;
+        let mut list = List::load(path)?;
+        list.lock_options = Some(options);
+        Ok(list)
+    }
+
+    /// Load the list at `path`, let `f` change it and save it, all while holding its lock.
+    ///
+    /// If the file does not exist, `f` receives an empty list. The lock is taken with the
+    /// default `LockOptions`; concurrent callers wait for each other instead of overwriting
+    /// each other's changes.
+    ///
+    /// Returns the value returned by `f`. Will error if the lock cannot be taken before the
+    /// timeout, or the file cannot be read or saved.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let open = List::with_locked("with_locked_doc.txt", |list| {
+    ///     list.add("Task 1");
+    ///     list.open_task_amount()
+    /// })
+    /// .unwrap();
+    /// assert_eq!(open, 1);
+    /// assert_eq!(List::load("with_locked_doc.txt").unwrap().task_amount(), 1);
+    /// # std::fs::remove_file("with_locked_doc.txt").unwrap();
+    /// ```
+    pub fn with_locked<P, F, R>(path: P, f: F) -> AnansiResult<R>
+    where
+        P: Into<PathBuf>,
+        F: FnOnce(&mut List) -> R,
+    {
+        let path = path.into();
+        let _lock = FileLock::acquire(&path, &LockOptions::default())?;
+        let mut list = match List::load(path.clone()) {
+            Ok(list) => list,
+            Err(AnansiError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                List::new_empty_with_path(path)
+            }
+            Err(err) => return Err(err),
+        };
+        let result = f(&mut list);
+        // The lock is already held, saving must not wait for it
+        list.lock_options = None;
+        list.save()?;
+        Ok(result)
+    }
+}
@@ -1,21 +1,126 @@
+mod append;
+mod archive;
+mod backend;
+mod backup;
 mod builder;
+mod completion;
+mod conflict;
+mod dirty;
+mod events;
+mod format;
+mod group;
+mod history;
+mod index;
+mod io;
+mod locked;
+mod order;
+mod renumber;
 mod test;
-
-use std::{collections::BTreeMap, ops::Deref, path::PathBuf};
-
-use builder::{build_default_list, deserialise_list, serialise_list};
-
-use crate::{AnansiError, Date, Task, error::AnansiResult, util::SortBy};
+mod transaction;
+mod transfer;
+mod uid;
+mod watch;
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::Arc,
+};
+
+pub use archive::Archive;
+pub use backup::BackupPolicy;
+#[cfg(feature = "bench")]
+pub use builder::deserialise_list_on;
+use builder::{build_default_list, serialise_list};
+pub use completion::Completion;
+pub use conflict::ConflictStrategy;
+use conflict::Snapshot;
+use events::EventQueue;
+pub use events::ListEvent;
+use format::TextFormat;
+pub(crate) use group::group_names;
+pub use group::{Group, GroupKey, GroupName};
+use history::{Change, History};
+use index::{TagIndex, ids_containing, ids_matching, normalise_tag, tag_counts};
+pub use transfer::TransferOptions;
+pub use watch::{ChangeSet, ReloadPolicy, WatchedList};
+
+use crate::{
+    AnansiError, Storage, Task,
+    error::AnansiResult,
+    util::{FileLock, LockOptions, SortSpec, sorted_indices},
+};
 
 type TaskID = usize;
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug)]
 pub struct List {
     file_path: PathBuf,
     tasks: BTreeMap<TaskID, Task>,
+    // Ids of all tasks in the order they are written to the file
+    order: Vec<TaskID>,
     open_tasks: Vec<TaskID>,
     done_tasks: Vec<TaskID>,
     max_id: Option<TaskID>,
+    index: TagIndex,
+    backup_policy: BackupPolicy,
+    snapshot: Option<Snapshot>,
+    conflict_strategy: ConflictStrategy,
+    lock_options: Option<LockOptions>,
+    history: History,
+    // Changes made by the open transactions, if any
+    journal: Option<Vec<Change>>,
+    events: EventQueue,
+    assign_uids: bool,
+    // Tasks changed since the last load or save
+    dirty_ids: BTreeSet<TaskID>,
+    // Where the list is saved, if not in the file at `file_path`
+    storage: Option<Arc<dyn Storage>>,
+    format: TextFormat,
+    // Done file that `save` archives done tasks to
+    archive_path: Option<PathBuf>,
+    // Order the tasks are sorted into before saving
+    sort_on_save: Option<SortSpec>,
+}
+
+// Lists are compared by their contents only; settings like the backup policy and derived data
+// like the tag index, the snapshot of the file, the history or events are ignored.
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.file_path == other.file_path
+            && self.tasks == other.tasks
+            && self.order == other.order
+            && self.open_tasks == other.open_tasks
+            && self.done_tasks == other.done_tasks
+            && self.max_id == other.max_id
+    }
+}
+
+impl PartialOrd for List {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.file_path.partial_cmp(&other.file_path) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.tasks.partial_cmp(&other.tasks) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.order.partial_cmp(&other.order) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.open_tasks.partial_cmp(&other.open_tasks) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.done_tasks.partial_cmp(&other.done_tasks) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.max_id.partial_cmp(&other.max_id)
+    }
 }
 
 impl From<Vec<Task>> for List {
@@ -35,6 +140,9 @@ impl List {
     }
 
     /// Returns the next available id.
+    ///
+    /// Ids start at 1, loaded tasks are numbered in file order. Ids of removed tasks are not
+    /// reused until `renumber` is called.
     pub fn max_id(&self) -> TaskID {
         self.max_id.unwrap_or(0) + 1
     }
@@ -45,8 +153,11 @@ impl List {
     }
 
     /// Updates the path to the list file.
+    ///
+    /// The new file is treated as never loaded, so the next `save` overwrites it.
     pub fn update_path<P: Into<PathBuf>>(&mut self, path: P) -> List {
         self.file_path = path.into();
+        self.snapshot = None;
         self.clone()
     }
 
@@ -72,14 +183,9 @@ impl List {
     /// Consider using `add` instead. Same behaviour, but the input is `&str` instead of `Task`.
     pub fn push_task(&mut self, task: Task) -> TaskID {
         let id = self.max_id();
-        let task = task.with_id(id);
-        self.max_id = Some(id);
-        if task.is_done() {
-            self.done_tasks.push(id);
-        } else {
-            self.open_tasks.push(id);
-        }
-        self.tasks.insert(id, task);
+        let task = self.with_uid(task.with_id(id), id, None);
+        let position = self.order.len();
+        self.apply(Change::Insert { id, task, position });
         id
     }
 
@@ -88,17 +194,83 @@ impl List {
     /// Will error if the id does not exist.
     ///
     /// The `id` of the passed in `Task` is ignored and the supplied `id` is used.
+    /// If `task` has no stable id (`uid:` tag), the one of the replaced task is kept.
     pub fn update_task(&mut self, task: Task, id: TaskID) -> AnansiResult<()> {
         if !self.is_id_used(id) {
             return Err(AnansiError::InvalidID(format!("ID {} does not exist", id)));
         }
-        let task = task.with_id(id);
-        if self.done_tasks.contains(&id) {
-            let idx = self.done_tasks.iter().position(|x| *x == id).unwrap();
+        let before = self.tasks[&id].clone();
+        let task = self.with_uid(task.with_id(id), id, Some(&before));
+        self.apply(Change::Update {
+            id,
+            before,
+            after: task,
+        });
+        Ok(())
+    }
+
+    fn new_empty_with_path<P: Into<PathBuf>>(path: P) -> List {
+        build_default_list(path)
+    }
+
+    /// Replaces the tasks of the list with the tasks of `loaded`, keeping all settings.
+    ///
+    /// The history is cleared, as the ids it refers to are no longer valid. All tasks are
+    /// dirty, as they no longer match the file as it was last loaded or saved; a reload marks
+    /// them clean again once it remembers the new contents.
+    fn adopt(&mut self, loaded: List) {
+        self.tasks = loaded.tasks;
+        self.order = loaded.order;
+        self.open_tasks = loaded.open_tasks;
+        self.done_tasks = loaded.done_tasks;
+        self.max_id = loaded.max_id;
+        self.index = loaded.index;
+        self.history.clear();
+        self.dirty_ids = self.order.iter().copied().collect();
+        self.events.push(ListEvent::Reloaded);
+    }
+
+    // Every change to the tasks of a list goes through the four functions below, which keep
+    // the order, the open and done ids and the tag index in sync.
+
+    fn insert_entry(&mut self, id: TaskID, task: Task, position: usize) {
+        self.max_id = Some(self.max_id.map_or(id, |max| max.max(id)));
+        self.order.insert(position.min(self.order.len()), id);
+        if task.is_done() {
+            self.done_tasks.push(id);
+        } else {
+            self.open_tasks.push(id);
+        }
+        self.index.insert(id, &task);
+        self.tasks.insert(id, task);
+    }
+
+    fn take_entry(&mut self, id: TaskID) -> Option<Task> {
+        let done_index = self.done_tasks.iter().position(|t| *t == id);
+        let open_index = if done_index.is_some() {
+            None
+        } else {
+            self.open_tasks.iter().position(|t| *t == id)
+        };
+        if let Some(open_index) = open_index {
+            self.open_tasks.swap_remove(open_index);
+        }
+        if let Some(done_index) = done_index {
+            self.done_tasks.swap_remove(done_index);
+        }
+        let task = self.tasks.remove(&id)?;
+        if let Some(position) = self.position(id) {
+            self.order.remove(position);
+        }
+        self.index.remove(id, &task);
+        Some(task)
+    }
+
+    fn replace_entry(&mut self, id: TaskID, task: Task) -> Option<Task> {
+        if let Some(idx) = self.done_tasks.iter().position(|x| *x == id) {
             self.done_tasks.remove(idx);
         }
-        if self.open_tasks.contains(&id) {
-            let idx = self.open_tasks.iter().position(|x| *x == id).unwrap();
+        if let Some(idx) = self.open_tasks.iter().position(|x| *x == id) {
             self.open_tasks.remove(idx);
         }
         if task.is_done() {
@@ -106,18 +278,16 @@ impl List {
         } else {
             self.open_tasks.push(id);
         }
-        self.tasks.insert(id, task);
-        Ok(())
+        if let Some(old) = self.tasks.get(&id) {
+            self.index.remove(id, old);
+        }
+        self.index.insert(id, &task);
+        self.tasks.insert(id, task)
     }
 
-    fn new_empty_with_path<P: Into<PathBuf>>(path: P) -> List {
-        List {
-            file_path: path.into(),
-            tasks: BTreeMap::new(),
-            open_tasks: Vec::new(),
-            done_tasks: Vec::new(),
-            max_id: None,
-        }
+    fn move_entry(&mut self, from: usize, to: usize) {
+        let id = self.order.remove(from);
+        self.order.insert(to, id);
     }
 
     /// Create a new list.
@@ -134,9 +304,9 @@ impl List {
     /// ```
     pub fn new<P: Into<PathBuf>>(path: P) -> List {
         let file_path = path.into();
-        if let Ok(file) = std::fs::read_to_string(&file_path) {
+        if let Ok(file) = std::fs::read(&file_path) {
             // load from file
-            deserialise_list(file_path, file.trim())
+            List::from_stored(file_path, None, &file)
         } else {
             // new list for new file
             build_default_list(file_path)
@@ -157,8 +327,8 @@ impl List {
     /// ```
     pub fn load<P: Into<PathBuf>>(path: P) -> AnansiResult<List> {
         let file_path = path.into();
-        match std::fs::read_to_string(&file_path) {
-            Ok(file) => Ok(deserialise_list(file_path, file.trim())),
+        match std::fs::read(&file_path) {
+            Ok(file) => Ok(List::from_stored(file_path, None, &file)),
             Err(err) => Err(err.into()),
         }
     }
@@ -197,19 +367,9 @@ impl List {
     /// ```
     pub fn remove<ID: Into<TaskID>>(&mut self, task_id: ID) {
         let id = task_id.into();
-        let done_index = self.done_tasks.iter().position(|t| *t == id);
-        let open_index = if done_index.is_some() {
-            None
-        } else {
-            self.open_tasks.iter().position(|t| *t == id)
-        };
-        if let Some(open_index) = open_index {
-            self.open_tasks.swap_remove(open_index);
-        }
-        if let Some(done_index) = done_index {
-            self.done_tasks.swap_remove(done_index);
+        if let Some(change) = self.removal(id) {
+            self.apply(change);
         }
-        self.tasks.remove(&id);
     }
 
     /// Get a task by id.
@@ -229,11 +389,9 @@ impl List {
 
     /// Sort tasks.
     ///
-    /// Provide a `SortBy` to sort by.
-    /// Possible values are:
-    /// - `SortBy::Priority`
-    /// - `SortBy::InceptionDate`
-    /// - `SortBy::CompletionDate`
+    /// Consumes the list; use `sorted` or `sorted_ids` to keep it.
+    ///
+    /// Provide a `SortBy`, `SortKey`, `SortTerm` or `SortSpec` to sort by.
     ///
     /// # Example
     /// ```
@@ -245,60 +403,52 @@ impl List {
     /// assert_eq!(sorted_tasks[0].to_string(), "(A) Task 2");
     /// assert_eq!(sorted_tasks[1].to_string(), "(B) Task 1");
     /// ```
-    pub fn sort(self, sort_by: SortBy) -> Vec<Task> {
-        match sort_by {
-            SortBy::Priority => self.sort_priority(),
-            SortBy::InceptionDate => self.sort_inception_date(),
-            SortBy::CompletionDate => self.sort_completion_date(),
-        }
-    }
-
-    fn sort_priority(self) -> Vec<Task> {
-        let mut tasks = self.tasks();
-        tasks.sort_by(|a, b| a.prio().cmp(&b.prio()));
-        let mut no_prio = Vec::new();
-        let mut with_prio = Vec::new();
-        for task in tasks {
-            if task.prio().is_none() {
-                no_prio.push(task);
-            } else {
-                with_prio.push(task);
-            }
-        }
-        with_prio.extend(no_prio);
-        with_prio
+    pub fn sort<S: Into<SortSpec>>(self, sort_by: S) -> Vec<Task> {
+        self.sorted(sort_by).into_iter().cloned().collect()
     }
 
-    fn sort_inception_date(self) -> Vec<Task> {
-        let mut tasks = self.tasks();
-        tasks.sort_by(|a, b| a.inception_date().cmp(&b.inception_date()));
-        let mut no_date = Vec::new();
-        let mut with_date = Vec::new();
-        for task in tasks {
-            if task.inception_date() == Date::default().to_string() {
-                no_date.push(task);
-            } else {
-                with_date.push(task);
-            }
-        }
-        with_date.extend(no_date);
-        with_date
+    /// Get references to all tasks in sorted order.
+    ///
+    /// Provide a `SortBy`, `SortKey`, `SortTerm` or `SortSpec` to sort by.
+    /// Sorting is stable; tasks equal in every key stay in list order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, SortKey, SortTerm};
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 +garden");
+    /// list.add("x Task 2 +house");
+    /// list.add("Task 3");
+    /// let sorted = list.sorted(SortTerm::new(SortKey::Project).descending());
+    /// assert_eq!(sorted[0].text(), "Task 2 +house");
+    /// assert_eq!(sorted[1].text(), "Task 1 +garden");
+    /// assert_eq!(sorted[2].text(), "Task 3");
+    /// ```
+    pub fn sorted<S: Into<SortSpec>>(&self, sort_by: S) -> Vec<&Task> {
+        let tasks: Vec<&Task> = self.ordered().collect();
+        sorted_indices(&tasks, &sort_by.into())
+            .into_iter()
+            .map(|idx| tasks[idx])
+            .collect()
     }
 
-    fn sort_completion_date(self) -> Vec<Task> {
-        let mut tasks = self.tasks();
-        tasks.sort_by(|a, b| a.completion_date().cmp(&b.completion_date()));
-        let mut no_date = Vec::new();
-        let mut with_date = Vec::new();
-        for task in tasks {
-            if task.completion_date() == Date::default().to_string() {
-                no_date.push(task);
-            } else {
-                with_date.push(task);
-            }
-        }
-        with_date.extend(no_date);
-        with_date
+    /// Get the ids of all tasks in sorted order.
+    ///
+    /// Same as `sorted`, but returns the ids instead of references.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, SortKey};
+    /// let mut list = List::new("path/to/list.txt");
+    /// let first = list.add("x Task 1");
+    /// let second = list.add("Task 2");
+    /// assert_eq!(list.sorted_ids(SortKey::Done), vec![second, first]);
+    /// ```
+    pub fn sorted_ids<S: Into<SortSpec>>(&self, sort_by: S) -> Vec<TaskID> {
+        self.sorted(sort_by)
+            .into_iter()
+            .map(|task| task.id())
+            .collect()
     }
 
     /// Get all tasks.
@@ -314,12 +464,22 @@ impl List {
     /// ```
     pub fn tasks(&self) -> Vec<Task> {
         let mut out = Vec::with_capacity(self.tasks.len());
-        for task in self.tasks.values() {
+        for task in self.ordered() {
             out.push(task.clone());
         }
         out
     }
 
+    /// Iterates over the ids and tasks in list order.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (TaskID, &Task)> {
+        self.order.iter().map(|id| (*id, &self.tasks[id]))
+    }
+
+    /// Iterates over the tasks in list order.
+    fn ordered(&self) -> impl Iterator<Item = &Task> {
+        self.order.iter().map(|id| &self.tasks[id])
+    }
+
     /// Get all done tasks.
     ///
     /// # Example
@@ -358,6 +518,27 @@ impl List {
 
     /// Save the list to the file.
     ///
+    /// The file is replaced atomically: the list is written to a temporary file next to it,
+    /// flushed to disk and renamed over the old file, so a crash or a full disk never leaves a
+    /// truncated file behind.
+    /// Before the file is replaced, a backup is created according to the `BackupPolicy`.
+    ///
+    /// If the only changes since the file was loaded or last saved are tasks added to the end
+    /// of the list, and the file was not changed since, the new tasks are appended to it
+    /// instead of writing the whole list.
+    ///
+    /// If the file was changed by someone else since it was loaded or last saved, the
+    /// `ConflictStrategy` decides what happens. Reloading or merging replaces the tasks of the
+    /// list with the merged tasks, which get new ids like on `load`.
+    ///
+    /// If locking is enabled with `set_lock_options`, the file is locked while saving.
+    /// If history persistence is enabled with `set_persist_history`, the history is saved next
+    /// to the file.
+    ///
+    /// Lists opened with `from_storage` are saved to their storage instead.
+    ///
+    /// If an archive path is set with `set_archive_path`, done tasks are moved there first.
+    ///
     /// # Example
     /// ```
     /// use anansi::List;
@@ -370,12 +551,28 @@ impl List {
     ///
     /// # let _ = std::fs::remove_file("list.txt");
     /// ```
-    pub fn save(&self) -> AnansiResult<()> {
-        let serialised = serialise_list(self);
-        match std::fs::write(&self.file_path, serialised) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e.into()),
+    pub fn save(&mut self) -> AnansiResult<()> {
+        let _lock = match (&self.lock_options, &self.storage) {
+            (Some(options), None) => Some(FileLock::acquire(&self.file_path, options)?),
+            _ => None,
+        };
+        let reconciled = self.reconcile()?;
+        self.auto_archive()?;
+        self.sort_before_save();
+        if self.storage.is_none() {
+            self.rotate_backups()?;
+        }
+        if reconciled || !self.append_new_tasks()? {
+            let serialised = serialise_list(self);
+            let encoded = self.encode(&serialised);
+            self.write_stored(&encoded)?;
+            let body = 0..serialised.len();
+            self.observe(&encoded, serialised, body);
+            self.format.issues.clear();
         }
+        self.dirty_ids.clear();
+        self.events.push(ListEvent::Saved);
+        self.save_history()
     }
 
     /// Filter tasks by priority.
@@ -405,7 +602,7 @@ impl List {
             .next()
             .expect("prio should not be empty");
         let mut filtered = Vec::new();
-        for task in self.tasks.values() {
+        for task in self.ordered() {
             if let Some(task_prio) = task.prio()
                 && task_prio == prio
             {
@@ -444,7 +641,7 @@ impl List {
     pub fn by_prio_to_vec(&self, prio: char) -> Vec<Task> {
         let prio = prio.to_uppercase().take(1).next().unwrap();
         let mut filtered = Vec::new();
-        for task in self.tasks.values() {
+        for task in self.ordered() {
             if let Some(task_prio) = task.prio()
                 && task_prio == prio
             {
@@ -478,7 +675,7 @@ impl List {
     pub fn by_text<S: Into<String>>(&self, text: S) -> List {
         let text = text.into().to_lowercase();
         let mut filtered = Vec::new();
-        for task in self.tasks.values() {
+        for task in self.ordered() {
             if task.text().to_lowercase().contains(&text) {
                 filtered.push(task.clone());
             }
@@ -517,7 +714,7 @@ impl List {
     pub fn by_text_to_vec(&self, text: &str) -> Vec<Task> {
         let text = text.to_lowercase();
         let mut filtered = Vec::new();
-        for task in self.tasks.values() {
+        for task in self.ordered() {
             if task.text().to_lowercase().contains(&text) {
                 filtered.push(task.clone());
             }
@@ -531,7 +728,7 @@ impl List {
     /// This will filter by checking if the predicate is contained within any context tag.
     /// This means that calling `by_context("air")` would return tasks with context tags `air` or `aircraft`.
     ///
-    /// The filter is case insensitive.
+    /// The filter is case insensitive. Tasks keep their list order.
     ///
     /// Can be chained with any other `by_*` method.
     ///
@@ -549,19 +746,10 @@ impl List {
     /// assert_eq!(list.by_context("craft").tasks().len(), 1);
     /// ```
     pub fn by_context<S: Into<String>>(&self, context: S) -> List {
-        let context = context.into();
-        let mut filtered = Vec::new();
-        for task in self.tasks.values() {
-            for tag in task.contexts() {
-                if tag.to_lowercase().contains(&context.to_lowercase()) {
-                    filtered.push(task.clone());
-                    break;
-                }
-            }
-        }
+        let context = normalise_tag(context.into());
         let mut filtered_list = List::new_empty_with_path(self.file_path.clone());
-        for task in filtered {
-            filtered_list.push_task(task);
+        for id in self.in_list_order(&ids_containing(self.index.contexts(), &context)) {
+            filtered_list.push_task(self.tasks[&id].clone());
         }
         filtered_list
     }
@@ -572,7 +760,7 @@ impl List {
     /// This will filter by checking if the predicate is contained within any project tag.
     /// This means that calling `by_project("air")` would return tasks with project tags `air` or `aircraft`.
     ///
-    /// The filter is case insensitive.
+    /// The filter is case insensitive. Tasks keep their list order.
     ///
     /// Can be chained with any other `by_*` method.
     ///
@@ -589,19 +777,10 @@ impl List {
     /// assert_eq!(list.by_project("home").tasks().len(), 3);
     /// assert_eq!(list.by_project("improvements").tasks().len(), 1);
     pub fn by_project<S: Into<String>>(&self, project: S) -> List {
-        let project = project.into();
-        let mut filtered = Vec::new();
-        for task in self.tasks.values() {
-            for tag in task.projects() {
-                if tag.to_lowercase().contains(&project.to_lowercase()) {
-                    filtered.push(task.clone());
-                    break;
-                }
-            }
-        }
+        let project = normalise_tag(project.into());
         let mut filtered_list = List::new_empty_with_path(self.file_path.clone());
-        for task in filtered {
-            filtered_list.push_task(task);
+        for id in self.in_list_order(&ids_containing(self.index.projects(), &project)) {
+            filtered_list.push_task(self.tasks[&id].clone());
         }
         filtered_list
     }
@@ -611,7 +790,7 @@ impl List {
     /// Will filter both open and done tasks.
     /// This will filter by checking if the predicate is contained within any special tag key.
     ///
-    /// The filter is case insensitive.
+    /// The filter is case insensitive. Tasks keep their list order.
     ///
     /// Can be chained with any other `by_*` method.
     ///
@@ -633,26 +812,131 @@ impl List {
     /// assert_eq!(list.by_special("assignment").tasks().len(), 1);
     /// ```
     pub fn by_special<S: Into<String>>(&self, special: S) -> List {
-        let special = special.into();
-        let mut filtered = Vec::new();
-        for task in self.tasks.values() {
-            for key in task.specials().keys() {
-                if key.to_lowercase().contains(&special.to_lowercase()) {
-                    filtered.push(task.clone());
-                    break;
-                }
-            }
-        }
+        let special = normalise_tag(special.into());
         let mut filtered_list = List::new_empty_with_path(self.file_path.clone());
-        for task in filtered {
-            filtered_list.push_task(task);
+        for id in self.in_list_order(&ids_containing(self.index.specials(), &special)) {
+            filtered_list.push_task(self.tasks[&id].clone());
         }
         filtered_list
     }
+
+    /// Get all context tags used in the list, with the amount of tasks using them.
+    ///
+    /// Tags are normalised to lowercase and returned in alphabetical order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 @home");
+    /// list.add("Task 2 @HOME @work");
+    /// list.add("x Task 3 @work");
+    ///
+    /// assert_eq!(list.all_contexts(), vec![("home", 2), ("work", 2)]);
+    /// ```
+    pub fn all_contexts(&self) -> Vec<(&str, usize)> {
+        tag_counts(self.index.contexts())
+    }
+
+    /// Get all project tags used in the list, with the amount of tasks using them.
+    ///
+    /// Tags are normalised to lowercase and returned in alphabetical order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 +garden");
+    /// list.add("Task 2 +Garden +house");
+    ///
+    /// assert_eq!(list.all_projects(), vec![("garden", 2), ("house", 1)]);
+    /// ```
+    pub fn all_projects(&self) -> Vec<(&str, usize)> {
+        tag_counts(self.index.projects())
+    }
+
+    /// Get all special tag keys used in the list, with the amount of tasks using them.
+    ///
+    /// Keys are normalised to lowercase and returned in alphabetical order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.add("Task 1 due:2020-01-01");
+    /// list.add("Task 2 DUE:2020-01-02 status:waiting");
+    ///
+    /// assert_eq!(list.all_special_keys(), vec![("due", 2), ("status", 1)]);
+    /// ```
+    pub fn all_special_keys(&self) -> Vec<(&str, usize)> {
+        tag_counts(self.index.specials())
+    }
+
+    /// Get the ids of all tasks with the given context tag.
+    ///
+    /// Unlike `by_context`, the tag has to match exactly. The match is case insensitive.
+    /// Ids are returned in list order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// let id = list.add("Task 1 @air");
+    /// list.add("Task 2 @aircraft");
+    ///
+    /// assert_eq!(list.ids_by_context("AIR"), vec![id]);
+    /// ```
+    pub fn ids_by_context(&self, context: &str) -> Vec<TaskID> {
+        self.in_list_order(&ids_matching(self.index.contexts(), context))
+    }
+
+    /// Get the ids of all tasks with the given project tag.
+    ///
+    /// Unlike `by_project`, the tag has to match exactly. The match is case insensitive.
+    /// Ids are returned in list order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// let id = list.add("Task 1 +home");
+    /// list.add("Task 2 +homeImprovements");
+    ///
+    /// assert_eq!(list.ids_by_project("home"), vec![id]);
+    /// ```
+    pub fn ids_by_project(&self, project: &str) -> Vec<TaskID> {
+        self.in_list_order(&ids_matching(self.index.projects(), project))
+    }
+
+    /// Get the ids of all tasks with the given special tag key.
+    ///
+    /// Unlike `by_special`, the key has to match exactly. The match is case insensitive.
+    /// Ids are returned in list order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// let id = list.add("Task 1 due:tomorrow");
+    /// list.add("Task 2 assignment_due:2020-01-01");
+    ///
+    /// assert_eq!(list.ids_by_special("due"), vec![id]);
+    /// ```
+    pub fn ids_by_special(&self, key: &str) -> Vec<TaskID> {
+        self.in_list_order(&ids_matching(self.index.specials(), key))
+    }
 }
 
 /// Sort a vector of tasks in place.
 ///
+/// Provide a `SortBy`, `SortKey`, `SortTerm` or `SortSpec` to sort by.
+///
 /// # Example
 /// ```
 /// use anansi::{List, vec::sort_vec_task, SortBy};
@@ -671,66 +955,13 @@ impl List {
 /// assert_eq!(sorted_tasks[0].to_string(), "(A) Task 1");
 /// assert_eq!(sorted_tasks[1].to_string(), "(B) Task 2");
 /// ```
-pub fn sort_vec_task(to_sort: &mut Vec<Task>, sort_by: SortBy) {
-    match sort_by {
-        SortBy::Priority => {
-            to_sort.sort_by(|a, b| a.prio().cmp(&b.prio()));
-            let mut no_prio = Vec::new();
-            let mut with_prio = Vec::new();
-            let len = to_sort.len();
-            for task in to_sort.iter() {
-                if task.prio().is_none() {
-                    no_prio.push(task);
-                } else {
-                    with_prio.push(task);
-                }
-            }
-            with_prio.extend(no_prio);
-            let mut out = Vec::with_capacity(len);
-            for task in with_prio {
-                out.push(task.clone());
-            }
-            *to_sort = out
-        }
-        SortBy::InceptionDate => {
-            to_sort.sort_by(|a, b| a.inception_date().cmp(&b.inception_date()));
-            let mut no_date = Vec::new();
-            let mut with_date = Vec::new();
-            let len = to_sort.len();
-            for task in to_sort.iter() {
-                if task.inception_date() == Date::default().to_string() {
-                    no_date.push(task);
-                } else {
-                    with_date.push(task);
-                }
-            }
-            with_date.extend(no_date);
-            let mut out = Vec::with_capacity(len);
-            for task in with_date {
-                out.push(task.clone());
-            }
-            *to_sort = out
-        }
-        SortBy::CompletionDate => {
-            to_sort.sort_by(|a, b| a.completion_date().cmp(&b.completion_date()));
-            let mut no_date = Vec::new();
-            let mut with_date = Vec::new();
-            let len = to_sort.len();
-            for task in to_sort.iter() {
-                if task.completion_date() == Date::default().to_string() {
-                    no_date.push(task);
-                } else {
-                    with_date.push(task);
-                }
-            }
-            with_date.extend(no_date);
-            let mut out = Vec::with_capacity(len);
-            for task in with_date {
-                out.push(task.clone());
-            }
-            *to_sort = out
-        }
-    }
+pub fn sort_vec_task<S: Into<SortSpec>>(to_sort: &mut Vec<Task>, sort_by: S) {
+    let order = {
+        let tasks: Vec<&Task> = to_sort.iter().collect();
+        sorted_indices(&tasks, &sort_by.into())
+    };
+    let mut tasks: Vec<Option<Task>> = to_sort.drain(..).map(Some).collect();
+    to_sort.extend(order.into_iter().filter_map(|idx| tasks[idx].take()));
 }
 
 /// Search a vector of tasks.
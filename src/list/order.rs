@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+
+use crate::{AnansiError, error::AnansiResult, util::SortSpec};
+
+use super::{List, TaskID, history::Change};
+
+impl List {
+    /// Returns the ids of all tasks in list order, the order they are saved in.
+    ///
+    /// Tasks are in the order of the file when loaded; added tasks go to the end.
+    pub fn ordered_ids(&self) -> &[TaskID] {
+        &self.order
+    }
+
+    /// Returns the position of the task with the given id in the list order.
+    pub fn position(&self, id: TaskID) -> Option<usize> {
+        self.order.iter().position(|other| *other == id)
+    }
+
+    /// Move a task to `index` in the list order, shifting the tasks after it.
+    ///
+    /// An index past the end moves the task to the end. Will error if the id does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::from_reader("Task 1\nTask 2\nTask 3\n".as_bytes()).unwrap();
+    /// list.move_to_index(3, 0).unwrap();
+    /// assert_eq!(list.ordered_ids(), &[3, 1, 2]);
+    /// ```
+    pub fn move_to_index(&mut self, id: TaskID, index: usize) -> AnansiResult<()> {
+        let from = self.existing_position(id)?;
+        let to = index.min(self.order.len() - 1);
+        if from != to {
+            self.apply(Change::Move { id, from, to });
+        }
+        Ok(())
+    }
+
+    /// Move a task right before another one.
+    ///
+    /// Will error if either id does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::from_reader("Task 1\nTask 2\nTask 3\n".as_bytes()).unwrap();
+    /// list.move_before(1, 3).unwrap();
+    /// assert_eq!(list.ordered_ids(), &[2, 1, 3]);
+    /// ```
+    pub fn move_before(&mut self, id: TaskID, target: TaskID) -> AnansiResult<()> {
+        let from = self.existing_position(id)?;
+        let target = self.existing_position(target)?;
+        self.move_to_index(id, if from < target { target - 1 } else { target })
+    }
+
+    /// Move a task right after another one.
+    ///
+    /// Will error if either id does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::from_reader("Task 1\nTask 2\nTask 3\n".as_bytes()).unwrap();
+    /// list.move_after(3, 1).unwrap();
+    /// assert_eq!(list.ordered_ids(), &[1, 3, 2]);
+    /// ```
+    pub fn move_after(&mut self, id: TaskID, target: TaskID) -> AnansiResult<()> {
+        let from = self.existing_position(id)?;
+        let target = self.existing_position(target)?;
+        self.move_to_index(id, if from <= target { target } else { target + 1 })
+    }
+
+    /// Swap the positions of two tasks, as a single undo step.
+    ///
+    /// Will error if either id does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::from_reader("Task 1\nTask 2\nTask 3\n".as_bytes()).unwrap();
+    /// list.swap(1, 3).unwrap();
+    /// assert_eq!(list.ordered_ids(), &[3, 2, 1]);
+    /// ```
+    pub fn swap(&mut self, a: TaskID, b: TaskID) -> AnansiResult<()> {
+        let (a, b) = (self.existing_position(a)?, self.existing_position(b)?);
+        let (first, second) = (a.min(b), a.max(b));
+        if first == second {
+            return Ok(());
+        }
+        let (first_id, second_id) = (self.order[first], self.order[second]);
+        self.begin_group();
+        // Moving the first task behind the second shifts the second one back by one
+        self.apply(Change::Move {
+            id: first_id,
+            from: first,
+            to: second,
+        });
+        self.apply(Change::Move {
+            id: second_id,
+            from: second - 1,
+            to: first,
+        });
+        self.end_group();
+        Ok(())
+    }
+
+    /// Reorder the list by `sort_by`, as a single undo step.
+    ///
+    /// Unlike `sort` and `sorted`, this changes the list order, so saving persists it.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, SortKey};
+    ///
+    /// let mut list = List::from_reader("(C) Task 1\n(A) Task 2\n(B) Task 3\n".as_bytes()).unwrap();
+    /// list.apply_sort(SortKey::Priority);
+    /// assert_eq!(list.ordered_ids(), &[2, 3, 1]);
+    ///
+    /// let mut output = Vec::new();
+    /// list.write_to(&mut output).unwrap();
+    /// assert_eq!(output, b"(A) Task 2\n(B) Task 3\n(C) Task 1\n");
+    /// ```
+    pub fn apply_sort<S: Into<SortSpec>>(&mut self, sort_by: S) {
+        let sorted = self.sorted_ids(sort_by);
+        self.begin_group();
+        for (to, id) in sorted.into_iter().enumerate() {
+            let Some(offset) = self.order[to..].iter().position(|other| *other == id) else {
+                continue;
+            };
+            if offset > 0 {
+                self.apply(Change::Move {
+                    id,
+                    from: to + offset,
+                    to,
+                });
+            }
+        }
+        self.end_group();
+    }
+
+    /// Returns the order the list is sorted into before every save, if any.
+    pub fn sort_on_save(&self) -> Option<&SortSpec> {
+        self.sort_on_save.as_ref()
+    }
+
+    /// Sets the order the list is sorted into before every save, as by `apply_sort`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, SortKey};
+    ///
+    /// let mut list = List::new("sort_on_save_doc.txt");
+    /// list.set_sort_on_save(Some(SortKey::Priority.into()));
+    /// list.add("(B) Task 1");
+    /// list.add("(A) Task 2");
+    /// list.save().unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string("sort_on_save_doc.txt").unwrap(),
+    ///     "(A) Task 2\n(B) Task 1\n"
+    /// );
+    /// # std::fs::remove_file("sort_on_save_doc.txt").unwrap();
+    /// ```
+    pub fn set_sort_on_save(&mut self, sort_by: Option<SortSpec>) {
+        self.sort_on_save = sort_by;
+    }
+
+    /// Sorts the list by the order set with `set_sort_on_save`, if any.
+    pub(super) fn sort_before_save(&mut self) {
+        if let Some(sort_by) = self.sort_on_save.clone() {
+            self.apply_sort(sort_by);
+        }
+    }
+
+    /// Returns the change removing the task with the given id, if it exists.
+    pub(super) fn removal(&self, id: TaskID) -> Option<Change> {
+        Some(Change::Remove {
+            id,
+            task: self.tasks.get(&id)?.clone(),
+            position: self.position(id)?,
+        })
+    }
+
+    /// Returns the given ids in list order.
+    pub(super) fn in_list_order(&self, ids: &BTreeSet<TaskID>) -> Vec<TaskID> {
+        self.order
+            .iter()
+            .copied()
+            .filter(|id| ids.contains(id))
+            .collect()
+    }
+
+    /// Returns the first of the given ids in list order.
+    pub(super) fn first_in_list_order(&self, ids: &BTreeSet<TaskID>) -> Option<TaskID> {
+        self.order.iter().copied().find(|id| ids.contains(id))
+    }
+
+    fn existing_position(&self, id: TaskID) -> AnansiResult<usize> {
+        self.position(id)
+            .ok_or_else(|| AnansiError::InvalidID(format!("ID {} does not exist", id)))
+    }
+}
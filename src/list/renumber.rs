@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use super::{List, ListEvent, TaskID, index::TagIndex};
+
+impl List {
+    /// Gives the tasks consecutive ids from 1 in list order, the ids they get when the list
+    /// is loaded again.
+    ///
+    /// Ids are never reused otherwise: removing tasks leaves gaps, and new tasks get ids after
+    /// the highest one used. Renumbering closes those gaps. The history is kept and refers to
+    /// the new ids; removed tasks it or the unsaved changes still refer to keep ids after those
+    /// of the tasks, so new tasks are numbered after them.
+    ///
+    /// Returns a map from the old to the new id of every task, so ids stored elsewhere, e.g.
+    /// in tags, can be updated. Does nothing inside a `transaction`, as it could not be
+    /// rolled back, and returns an empty map.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::from_reader("Task 1\nTask 2\nTask 3\n".as_bytes()).unwrap();
+    /// list.remove(2usize);
+    /// let id = list.add("Task 4");
+    /// assert_eq!(id, 4);
+    ///
+    /// let ids = list.renumber();
+    /// assert_eq!(ids[&3], 2);
+    /// assert_eq!(ids[&4], 3);
+    /// assert_eq!(list.get(3).unwrap().text(), "Task 4");
+    /// ```
+    pub fn renumber(&mut self) -> BTreeMap<TaskID, TaskID> {
+        if self.journal.is_some() {
+            return BTreeMap::new();
+        }
+        let mut ids = self.compacted_ids();
+        let renumbered = ids.clone();
+        // Tasks that only exist in the history or in the last saved file keep an id of their
+        // own, after those of the current tasks
+        self.history.remap(&mut ids);
+        if let Some(snapshot) = &mut self.snapshot {
+            snapshot.remap(&mut ids);
+        }
+        self.dirty_ids = self
+            .dirty_ids
+            .iter()
+            .map(|id| remapped(&mut ids, *id))
+            .collect();
+
+        let tasks = std::mem::take(&mut self.tasks);
+        self.tasks = tasks
+            .into_iter()
+            .map(|(id, task)| (ids[&id], task.with_id(ids[&id])))
+            .collect();
+        for id in self
+            .order
+            .iter_mut()
+            .chain(&mut self.open_tasks)
+            .chain(&mut self.done_tasks)
+        {
+            *id = ids[id];
+        }
+        self.index = TagIndex::default();
+        for (id, task) in &self.tasks {
+            self.index.insert(*id, task);
+        }
+        self.max_id = ids.values().max().copied();
+        self.events.push(ListEvent::Renumbered {
+            ids: renumbered.clone(),
+        });
+        renumbered
+    }
+
+    /// Returns the ids the tasks get when the list is loaded again, by their current id.
+    pub(super) fn compacted_ids(&self) -> BTreeMap<TaskID, TaskID> {
+        self.order
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position + 1))
+            .collect()
+    }
+}
+
+/// Returns the new id of `id`, giving it the next free one if it has none yet.
+pub(super) fn remapped(ids: &mut BTreeMap<TaskID, TaskID>, id: TaskID) -> TaskID {
+    let next = ids.len() + 1;
+    *ids.entry(id).or_insert(next)
+}
@@ -333,3 +333,495 @@ fn sort_by_completion_date_non_unique() {
         "x 2022-02-18 2022-01-08 (A) Task 8"
     );
 }
+
+#[test]
+fn tag_index_follows_mutations() {
+    let mut list = List::new("tag_index.txt");
+    let first = list.add("Task 1 +garden @home due:2020-01-01");
+    let second = list.add("Task 2 +Garden @work");
+    assert_eq!(list.all_projects(), vec![("garden", 2)]);
+    assert_eq!(list.all_contexts(), vec![("home", 1), ("work", 1)]);
+
+    list.update_task(crate::Task::new("Task 1 +house @home", 0), first)
+        .unwrap();
+    assert_eq!(list.all_projects(), vec![("garden", 1), ("house", 1)]);
+    assert!(list.all_special_keys().is_empty());
+    assert_eq!(list.ids_by_project("garden"), vec![second]);
+
+    list.remove(second);
+    assert_eq!(list.all_projects(), vec![("house", 1)]);
+    assert_eq!(list.all_contexts(), vec![("home", 1)]);
+    assert!(list.by_context("work").tasks().is_empty());
+}
+
+#[test]
+fn complete_contexts_and_special_keys() {
+    let mut list = List::new("completion.txt");
+    list.add("Task 1 @Home due:2020-01-01");
+    list.add("Task 2 @home @hobby");
+    list.add("x 2021-05-05 2021-01-01 Task 3 @hobby duration:2h");
+
+    let completions = list.complete("Call mum @ho", 12);
+    assert_eq!(completions.len(), 2);
+    // Both are used twice, `@hobby` more recently
+    assert_eq!(completions[0].text(), "@hobby");
+    assert_eq!(completions[0].last_used(), crate::Date::new(2021, 5, 5));
+    assert_eq!(completions[1].text(), "@Home");
+    assert_eq!(completions[1].uses(), 2);
+
+    let completions = list.complete("du", 2);
+    assert_eq!(completions.len(), 2);
+    assert_eq!(completions[0].text(), "duration:");
+    assert_eq!(completions[1].text(), "due:");
+
+    assert!(list.complete("Call mum ", 9).is_empty());
+    assert!(list.complete("+nothing", 8).is_empty());
+
+    // The spelling follows the list order, not the ids
+    let later = list.add("Task 4 DUE:2020-02-02");
+    assert_eq!(list.complete("du", 2)[0].text(), "due:");
+    list.move_to_index(later, 0).unwrap();
+    list.move_to_index(2, 0).unwrap();
+    assert_eq!(list.complete("du", 2)[0].text(), "DUE:");
+    assert_eq!(list.complete("@ho", 3)[1].text(), "@home");
+}
+
+#[test]
+fn sort_multi_key_typed_specials() {
+    use crate::{SortKey, SortSpec, SortTerm};
+
+    let mut list = List::new("sort_spec.txt");
+    let a = list.add("Task a estimate:10 +b");
+    let b = list.add("Task b estimate:9 +a");
+    let c = list.add("Task c +a");
+    let d = list.add("x Task d estimate:9 +a");
+
+    // Numbers compare numerically, not as text
+    assert_eq!(
+        list.sorted_ids(SortKey::Special("estimate".to_string())),
+        vec![b, d, a, c]
+    );
+    assert_eq!(
+        list.sorted_ids(
+            SortTerm::new(SortKey::Special("estimate".to_string()))
+                .descending()
+                .nulls_first()
+        ),
+        vec![c, a, b, d]
+    );
+
+    // Mixed values sort numbers first, then dates, then text
+    let mut mixed = List::new("sort_spec_mixed.txt");
+    let text = mixed.add("Task size:Large");
+    let date = mixed.add("Task size:2024-01-01");
+    let small = mixed.add("Task size:3");
+    let large = mixed.add("Task size:20");
+    let other_text = mixed.add("Task size:huge");
+    assert_eq!(
+        mixed.sorted_ids(SortKey::Special("size".to_string())),
+        vec![small, large, date, other_text, text]
+    );
+
+    let spec = SortSpec::new()
+        .then(SortKey::Project)
+        .then(SortTerm::new(SortKey::Done).descending())
+        .then(SortTerm::new(SortKey::Id).descending());
+    assert_eq!(list.sorted_ids(spec.clone()), vec![d, c, b, a]);
+
+    let mut tasks = list.tasks();
+    crate::vec::sort_vec_task(&mut tasks, spec);
+    let ids: Vec<usize> = tasks.iter().map(|t| t.id()).collect();
+    assert_eq!(ids, vec![d, c, b, a]);
+}
+
+#[test]
+fn group_by_completion_month_and_special() {
+    use crate::{GroupKey, GroupName};
+
+    let mut list = List::new("grouping.txt");
+    let a = list.add("x 2022-02-18 2022-01-08 Task a status:waiting");
+    let b = list.add("x 2022-02-01 2022-01-08 Task b");
+    let c = list.add("x 2022-03-01 2022-01-08 Task c STATUS:Waiting");
+    let d = list.add("Task d status:active");
+
+    let months = list.group_by(GroupKey::CompletionMonth);
+    assert_eq!(months.len(), 3);
+    assert_eq!(
+        months[&GroupName::Value("2022-02".to_string())].ids(),
+        &[a, b]
+    );
+    assert_eq!(months[&GroupName::Value("2022-03".to_string())].ids(), &[c]);
+    assert_eq!(months[&GroupName::None].ids(), &[d]);
+
+    let status = list.group_by_nested(&[GroupKey::Special("status".to_string()), GroupKey::Done]);
+    let waiting = &status[&GroupName::Value("waiting".to_string())];
+    assert_eq!(waiting.ids(), &[a, c]);
+    assert_eq!(waiting.done(), 2);
+    assert_eq!(waiting.open(), 0);
+    assert_eq!(waiting.subgroups().len(), 1);
+    assert_eq!(status[&GroupName::None].ids(), &[b]);
+}
+
+#[test]
+fn undo_redo_keeps_lists_and_index_in_sync() {
+    use crate::Task;
+
+    let mut list = List::new("undo_redo.txt");
+    list.set_history_depth(2);
+    let a = list.add("Task a +garden");
+    let b = list.add("Task b");
+    list.update_task(Task::new("x Task b @home", b), b).unwrap();
+    assert!(list.can_undo());
+
+    assert!(list.undo());
+    assert_eq!(list.get(b).unwrap().to_string(), "Task b");
+    assert_eq!(list.open_task_amount(), 2);
+    assert!(list.ids_by_context("home").is_empty());
+    assert!(list.undo());
+    assert_eq!(list.task_amount(), 1);
+    // The first `add` fell out of the history
+    assert!(!list.undo());
+
+    assert!(list.redo());
+    assert!(list.redo());
+    assert!(!list.can_redo());
+    assert_eq!(list.done_task_amount(), 1);
+    assert_eq!(list.ids_by_context("home"), vec![b]);
+
+    list.begin_group();
+    list.remove(a);
+    list.begin_group();
+    list.remove(b);
+    list.end_group();
+    list.end_group();
+    assert_eq!(list.task_amount(), 0);
+    assert!(list.undo());
+    assert_eq!(list.task_amount(), 2);
+    assert_eq!(list.ids_by_project("garden"), vec![a]);
+    // New changes clear the steps to redo
+    list.add("Task c");
+    assert!(!list.can_redo());
+}
+
+#[test]
+fn transaction_rolls_back_everything() {
+    use crate::{AnansiError, Task};
+
+    let mut list = List::new("transaction.txt");
+    list.set_history_depth(10);
+    let a = list.add("Task a +garden");
+    let b = list.add("x Task b");
+    let open_before = list.open();
+    let done_before = list.done();
+
+    let result: Result<(), AnansiError> = list.transaction(|tx| {
+        tx.remove(a);
+        tx.update_task(Task::new("Task b @home", b), b)?;
+        tx.add("Task c +garden");
+        Err(AnansiError::Generic("abort".to_string()))
+    });
+    assert!(result.is_err());
+    assert_eq!(list.open(), open_before);
+    assert_eq!(list.done(), done_before);
+    assert_eq!(list.max_id(), b + 1);
+    assert_eq!(list.ids_by_project("garden"), vec![a]);
+    assert!(list.ids_by_context("home").is_empty());
+
+    // A failing inner transaction only rolls back its own changes
+    list.transaction(|tx| {
+        tx.add("Task c");
+        let inner = tx.transaction(|inner| {
+            inner.remove(a);
+            inner.update_task(Task::new("Task d", 42), 42)
+        });
+        assert!(inner.is_err());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(list.task_amount(), 3);
+    assert!(list.get(a).is_some());
+
+    // A successful transaction is undone as one step
+    list.transaction(|tx| {
+        tx.remove(a);
+        tx.remove(b);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(list.task_amount(), 1);
+    assert!(list.undo());
+    assert_eq!(list.task_amount(), 3);
+}
+
+#[test]
+fn events_follow_mutations() {
+    use crate::{AnansiError, ListEvent};
+
+    let mut list = List::new("events.txt");
+    list.add("Task a");
+    list.set_emit_events(true);
+    list.set_history_depth(10);
+    let b = list.add("Task b");
+    list.remove(b);
+    list.undo();
+    let events = list.drain_events();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(&events[0], ListEvent::TaskAdded { id, .. } if *id == b));
+    assert!(matches!(&events[1], ListEvent::TaskRemoved { id, .. } if *id == b));
+    assert!(matches!(&events[2], ListEvent::TaskAdded { task, .. } if task.text() == "Task b"));
+
+    // Unseen events of a failed transaction are dropped
+    let _ = list.transaction(|tx| -> Result<(), AnansiError> {
+        tx.add("Task c");
+        Err(AnansiError::Generic("abort".to_string()))
+    });
+    assert!(list.drain_events().is_empty());
+
+    // Seen events are followed by the reverting events
+    let _ = list.transaction(|tx| -> Result<(), AnansiError> {
+        tx.add("Task c");
+        assert_eq!(tx.drain_events().len(), 1);
+        Err(AnansiError::Generic("abort".to_string()))
+    });
+    let events = list.drain_events();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], ListEvent::TaskRemoved { task, .. } if task.text() == "Task c"));
+
+    list.save().unwrap();
+    list.reload().unwrap();
+    assert_eq!(
+        list.drain_events(),
+        vec![ListEvent::Saved, ListEvent::Reloaded]
+    );
+    std::fs::remove_file("events.txt").unwrap();
+}
+
+#[test]
+fn uids_are_stable() {
+    use crate::Task;
+
+    let mut list = List::new("uids.txt");
+    let plain = list.add("Task a");
+    let duplicate = list.add("Task b ref:uid:same uid:same");
+    let original = list.add("Task c uid:same");
+    list.set_assign_uids(true);
+    let uid = list.get(plain).unwrap().uid().unwrap().to_string();
+    assert_eq!(uid.len(), 12);
+    assert_eq!(list.id_by_uid(&uid), Some(plain));
+    // Duplicates get a fresh uid, the last task keeps it
+    assert_eq!(list.id_by_uid("same"), Some(original));
+    assert_ne!(list.get(duplicate).unwrap().uid(), Some("same"));
+    // Only the uid tag itself is replaced
+    let fresh = list.get(duplicate).unwrap().uid().unwrap().to_string();
+    assert_eq!(
+        list.get(duplicate).unwrap().text(),
+        format!("Task b ref:uid:same uid:{}", fresh)
+    );
+
+    let undone = list.get(original).unwrap().done(None).unwrap().undone();
+    list.update_task(undone, original).unwrap();
+    assert_eq!(list.id_by_uid("same"), Some(original));
+
+    list.save().unwrap();
+    let mut reloaded = List::load("uids.txt").unwrap();
+    assert_eq!(
+        reloaded.get_by_uid(&uid).unwrap().text(),
+        format!("Task a uid:{}", uid)
+    );
+    let id = reloaded.id_by_uid(&uid).unwrap();
+    reloaded.remove(id);
+    assert!(reloaded.get_by_uid(&uid).is_none());
+    std::fs::remove_file("uids.txt").unwrap();
+
+    // Without assigning, uids of replaced tasks are still kept
+    let mut list = List::new("uids.txt");
+    let id = list.add("Task d uid:keep");
+    list.update_task(Task::new("Task e", 0), id).unwrap();
+    assert_eq!(list.get(id).unwrap().uid(), Some("keep"));
+}
+
+#[test]
+fn dirty_tracking() {
+    use crate::{AnansiError, Task};
+
+    std::fs::write("dirty.txt", "Task a\nTask b\n").unwrap();
+    let mut list = List::load("dirty.txt").unwrap();
+    assert!(!list.is_dirty());
+    let ids: Vec<usize> = list.tasks().iter().map(|task| task.id()).collect();
+
+    list.update_task(Task::new("x Task a", 0), ids[0]).unwrap();
+    list.remove(ids[1]);
+    let added = list.add("Task c");
+    assert_eq!(list.changed_ids(), vec![ids[0], ids[1], added]);
+
+    assert!(list.save_if_dirty().unwrap());
+    assert!(list.changed_ids().is_empty());
+
+    // A failed transaction leaves the list clean
+    let _ = list.transaction(|tx| -> Result<(), AnansiError> {
+        tx.add("Task d");
+        Err(AnansiError::Generic("abort".to_string()))
+    });
+    assert!(!list.is_dirty());
+    assert!(!list.save_if_dirty().unwrap());
+    std::fs::remove_file("dirty.txt").unwrap();
+}
+
+#[test]
+fn transfers_keep_both_lists_in_sync() {
+    use crate::TransferOptions;
+
+    let mut work = List::from_reader("Task a +report\nx Task b\nTask c\n".as_bytes()).unwrap();
+    let mut home = List::from_reader("Task d\n".as_bytes()).unwrap();
+    work.set_history_depth(10);
+    home.set_history_depth(10);
+
+    assert!(work.move_to(&mut home, [1, 9]).is_err());
+    assert_eq!(work.task_amount(), 3);
+    assert_eq!(home.task_amount(), 1);
+
+    let options = TransferOptions::new().with_provenance("work");
+    let ids = work.move_to_with(&mut home, [3, 1, 3], &options).unwrap();
+    assert_eq!(ids, vec![2, 3]);
+    assert_eq!(home.get(3).unwrap().to_string(), "Task a +report from:work");
+    assert_eq!(home.ids_by_project("report"), vec![3]);
+    assert!(work.ids_by_project("report").is_empty());
+    assert_eq!(work.done_task_amount(), 1);
+
+    // One undo step per list
+    work.undo();
+    home.undo();
+    assert_eq!(work.task_amount(), 3);
+    assert_eq!(home.task_amount(), 1);
+    assert!(!work.can_undo() && !home.can_undo());
+
+    let ids = work.copy_to(&mut home, [2]).unwrap();
+    assert!(home.get(ids[0]).unwrap().is_done());
+    assert_eq!(work.task_amount(), 3);
+
+    // Only the from tag itself is replaced
+    let id = home.add("Task e xfrom:home via:from:home from:home");
+    let ids = home.copy_to_with(&mut work, [id], &options).unwrap();
+    assert_eq!(
+        work.get(ids[0]).unwrap().to_string(),
+        "Task e xfrom:home via:from:home from:work"
+    );
+}
+
+#[test]
+fn manual_order_is_undoable_and_saved() {
+    use crate::SortKey;
+
+    let mut list =
+        List::from_reader("(C) Task a\n(A) Task b\nTask c\n(B) Task d\n".as_bytes()).unwrap();
+    list.set_history_depth(10);
+
+    list.move_to_index(4, 99).unwrap();
+    assert_eq!(list.ordered_ids(), &[1, 2, 3, 4]);
+    assert!(!list.can_undo());
+    assert!(list.move_before(8, 1).is_err());
+
+    list.move_before(4, 1).unwrap();
+    list.move_after(2, 3).unwrap();
+    assert_eq!(list.ordered_ids(), &[4, 1, 3, 2]);
+    list.swap(4, 2).unwrap();
+    assert_eq!(list.ordered_ids(), &[2, 1, 3, 4]);
+    list.undo();
+    assert_eq!(list.ordered_ids(), &[4, 1, 3, 2]);
+
+    // Removed tasks come back at their old position
+    list.remove(1usize);
+    assert_eq!(list.ordered_ids(), &[4, 3, 2]);
+    list.undo();
+    assert_eq!(list.ordered_ids(), &[4, 1, 3, 2]);
+
+    list.apply_sort(SortKey::Priority);
+    assert_eq!(list.ordered_ids(), &[2, 4, 1, 3]);
+    list.undo();
+    assert_eq!(list.ordered_ids(), &[4, 1, 3, 2]);
+    list.redo();
+
+    let mut output = Vec::new();
+    list.write_to(&mut output).unwrap();
+    assert_eq!(output, b"(A) Task b\n(B) Task d\n(C) Task a\nTask c\n");
+}
+
+#[test]
+fn tag_filters_keep_list_order() {
+    let mut list = List::from_reader(
+        "Task a @home +air due:1\nTask b @home +air due:2\nTask c @home +air due:3\n".as_bytes(),
+    )
+    .unwrap();
+    list.move_to_index(3, 0).unwrap();
+    list.move_to_index(1, 99).unwrap();
+    assert_eq!(list.ordered_ids(), &[3, 2, 1]);
+
+    assert_eq!(list.ids_by_context("home"), vec![3, 2, 1]);
+    assert_eq!(list.ids_by_project("air"), vec![3, 2, 1]);
+    assert_eq!(list.ids_by_special("due"), vec![3, 2, 1]);
+    let names = |filtered: List| -> Vec<String> {
+        filtered.ordered().map(|task| task.to_string()).collect()
+    };
+    let expected = vec![
+        "Task c @home +air due:3",
+        "Task b @home +air due:2",
+        "Task a @home +air due:1",
+    ];
+    assert_eq!(names(list.by_context("hom")), expected);
+    assert_eq!(names(list.by_project("ai")), expected);
+    assert_eq!(names(list.by_special("du")), expected);
+}
+
+#[test]
+fn renumber_keeps_history_and_index() {
+    use crate::ListEvent;
+
+    let mut list = List::from_reader("Task a @home\nTask b\nTask c @home\n".as_bytes()).unwrap();
+    assert_eq!(list.ordered_ids(), &[1, 2, 3]);
+    list.set_history_depth(10);
+    list.remove(1usize);
+    let id = list.add("Task d @home");
+    list.move_before(id, 2).unwrap();
+    list.set_emit_events(true);
+
+    let ids = list.renumber();
+    assert_eq!(
+        ids.into_iter().collect::<Vec<_>>(),
+        vec![(2, 2), (3, 3), (4, 1)]
+    );
+    assert_eq!(list.ordered_ids(), &[1, 2, 3]);
+    assert_eq!(list.ids_by_context("home"), vec![1, 3]);
+    assert!(matches!(
+        list.drain_events().as_slice(),
+        [ListEvent::Renumbered { .. }]
+    ));
+
+    // The removed task gets an id after the others and comes back at its old place
+    list.undo();
+    list.undo();
+    list.undo();
+    let tasks = list.tasks();
+    let texts: Vec<&str> = tasks.iter().map(|task| task.text()).collect();
+    assert_eq!(texts, vec!["Task a @home", "Task b", "Task c @home"]);
+    assert_eq!(list.ordered_ids(), &[4, 2, 3]);
+    assert!(list.add("Task e") > 4);
+
+    assert!(list.transaction(|tx| Ok(tx.renumber())).unwrap().is_empty());
+}
+
+#[test]
+fn parallel_parsing_keeps_line_order() {
+    use super::builder::parse_parallel;
+    use crate::Task;
+
+    let contents = std::fs::read_to_string("test-data/complex-todo.txt").unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    let sequential: Vec<Task> = lines
+        .iter()
+        .enumerate()
+        .map(|(n, line)| Task::new(line, n + 1))
+        .collect();
+    for threads in [1, 3, lines.len() + 1] {
+        assert_eq!(parse_parallel(&lines, threads), sequential);
+    }
+}
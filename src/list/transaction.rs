@@ -0,0 +1,76 @@
+use crate::error::AnansiResult;
+
+use super::List;
+
+impl List {
+    /// Run `f` as a transaction: if it returns an error, all changes it made to the tasks are
+    /// rolled back, restoring the open and done tasks, the next id, the history and the
+    /// dirty state.
+    ///
+    /// On success, the changes are kept and form a single undoable step.
+    /// Transactions can be nested; an inner transaction that fails only rolls back its own
+    /// changes. Only the tasks are rolled back, a file saved inside `f` is not restored.
+    ///
+    /// Returns the result of `f`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, Task};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// let id = list.add("Task 1 +garden");
+    ///
+    /// let result = list.transaction(|tx| {
+    ///     tx.update_task(Task::new("x Task 1 +garden", id), id)?;
+    ///     tx.add("Task 2 +garden");
+    ///     // Fails, there is no task 42
+    ///     tx.update_task(Task::new("x Task 42 +garden", 42), 42)
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(list.task_amount(), 1);
+    /// assert_eq!(list.open_task_amount(), 1);
+    /// assert_eq!(list.max_id(), id + 1);
+    /// ```
+    pub fn transaction<F, R>(&mut self, f: F) -> AnansiResult<R>
+    where
+        F: FnOnce(&mut List) -> AnansiResult<R>,
+    {
+        let outermost = self.journal.is_none();
+        let start = self.journal.get_or_insert_with(Vec::new).len();
+        let order = self.order.clone();
+        let open_tasks = self.open_tasks.clone();
+        let done_tasks = self.done_tasks.clone();
+        let max_id = self.max_id;
+        let history = self.history.clone();
+        let events = self.events.mark();
+        let dirty_ids = self.dirty_ids.clone();
+
+        self.begin_group();
+        let result = f(self);
+        if result.is_ok() {
+            self.end_group();
+        } else {
+            let mut journal = self.journal.take().unwrap_or_default();
+            let made = journal.split_off(start.min(journal.len()));
+            // Events nobody saw yet are dropped, otherwise the reverting changes are reported
+            let report = !self.events.forget_since(events);
+            let emit = self.events.is_enabled();
+            self.events.set_enabled(emit && report);
+            for change in made.into_iter().rev() {
+                self.apply_entry(change.inverse());
+            }
+            self.events.set_enabled(emit);
+            self.journal = Some(journal);
+            self.order = order;
+            self.open_tasks = open_tasks;
+            self.done_tasks = done_tasks;
+            self.max_id = max_id;
+            self.history = history;
+            self.dirty_ids = dirty_ids;
+        }
+        if outermost {
+            self.journal = None;
+        }
+        result
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeSet;
+
+use crate::{AnansiError, Task, error::AnansiResult, util::replace_token};
+
+use super::{List, TaskID};
+
+/// Options for moving and copying tasks between lists.
+///
+/// # Example
+/// ```
+/// use anansi::TransferOptions;
+///
+/// let options = TransferOptions::new().with_provenance("work");
+/// assert_eq!(options.provenance(), Some("work"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferOptions {
+    provenance: Option<String>,
+}
+
+impl TransferOptions {
+    /// Creates the default options: tasks are transferred unchanged.
+    pub fn new() -> TransferOptions {
+        TransferOptions::default()
+    }
+
+    /// Tag transferred tasks with `from:<source>`, replacing an existing `from` tag.
+    pub fn with_provenance<S: Into<String>>(mut self, source: S) -> TransferOptions {
+        self.provenance = Some(source.into());
+        self
+    }
+
+    /// Returns the name transferred tasks are tagged with, if any.
+    pub fn provenance(&self) -> Option<&str> {
+        self.provenance.as_deref()
+    }
+
+    pub(crate) fn apply(&self, mut task: Task) -> Task {
+        let Some(source) = &self.provenance else {
+            return task;
+        };
+        let text = match task.special("from") {
+            Some(old) => replace_token(
+                task.text(),
+                &format!("from:{}", old),
+                &format!("from:{}", source),
+            ),
+            None if task.text().is_empty() => format!("from:{}", source),
+            None => format!("{} from:{}", task.text(), source),
+        };
+        task.update_text(text);
+        task
+    }
+}
+
+impl List {
+    /// Move the tasks with the given ids to the end of `other`.
+    ///
+    /// Same as `move_to_with` with the default `TransferOptions`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut work = List::from_reader("Task 1\nTask 2\n".as_bytes()).unwrap();
+    /// let mut home = List::from_reader("Task 3\n".as_bytes()).unwrap();
+    ///
+    /// let ids = work.move_to(&mut home, [2]).unwrap();
+    /// assert_eq!(ids, vec![2]);
+    /// assert_eq!(home.get(2).unwrap().text(), "Task 2");
+    /// assert_eq!(work.task_amount(), 1);
+    /// ```
+    pub fn move_to<I: IntoIterator<Item = TaskID>>(
+        &mut self,
+        other: &mut List,
+        ids: I,
+    ) -> AnansiResult<Vec<TaskID>> {
+        self.move_to_with(other, ids, &TransferOptions::default())
+    }
+
+    /// Move the tasks with the given ids to the end of `other`, in the given order.
+    ///
+    /// The tasks get new ids in `other`, like tasks added with `push_task`. In each list, the
+    /// transfer is a single undo step, so undoing it in this list alone brings the tasks back
+    /// while `other` keeps them; undo `other` as well, or move tasks between the lists of a
+    /// `Workspace` and use `Workspace::undo_transfer`.
+    ///
+    /// Returns the new ids. Will error without changing either list if an id does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, TransferOptions};
+    ///
+    /// let mut work = List::from_reader("Task 1\nTask 2\n".as_bytes()).unwrap();
+    /// let mut home = List::from_reader("".as_bytes()).unwrap();
+    /// work.set_history_depth(10);
+    ///
+    /// let options = TransferOptions::new().with_provenance("work");
+    /// let ids = work.move_to_with(&mut home, [1, 2], &options).unwrap();
+    /// assert_eq!(home.get(ids[0]).unwrap().text(), "Task 1 from:work");
+    ///
+    /// work.undo();
+    /// assert_eq!(work.task_amount(), 2);
+    /// ```
+    pub fn move_to_with<I: IntoIterator<Item = TaskID>>(
+        &mut self,
+        other: &mut List,
+        ids: I,
+        options: &TransferOptions,
+    ) -> AnansiResult<Vec<TaskID>> {
+        let tasks = self.transferred(ids)?;
+        self.begin_group();
+        for (id, _) in &tasks {
+            if let Some(change) = self.removal(*id) {
+                self.apply(change);
+            }
+        }
+        self.end_group();
+        Ok(other.receive(tasks, options))
+    }
+
+    /// Copy the tasks with the given ids to the end of `other`.
+    ///
+    /// Same as `copy_to_with` with the default `TransferOptions`.
+    pub fn copy_to<I: IntoIterator<Item = TaskID>>(
+        &self,
+        other: &mut List,
+        ids: I,
+    ) -> AnansiResult<Vec<TaskID>> {
+        self.copy_to_with(other, ids, &TransferOptions::default())
+    }
+
+    /// Copy the tasks with the given ids to the end of `other`, in the given order.
+    ///
+    /// Like `move_to_with`, but the tasks stay in this list.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, TransferOptions};
+    ///
+    /// let work = List::from_reader("Task 1 from:inbox\n".as_bytes()).unwrap();
+    /// let mut home = List::from_reader("".as_bytes()).unwrap();
+    ///
+    /// let options = TransferOptions::new().with_provenance("work");
+    /// let ids = work.copy_to_with(&mut home, [1], &options).unwrap();
+    /// assert_eq!(home.get(ids[0]).unwrap().text(), "Task 1 from:work");
+    /// assert_eq!(work.task_amount(), 1);
+    /// ```
+    pub fn copy_to_with<I: IntoIterator<Item = TaskID>>(
+        &self,
+        other: &mut List,
+        ids: I,
+        options: &TransferOptions,
+    ) -> AnansiResult<Vec<TaskID>> {
+        let tasks = self.transferred(ids)?;
+        Ok(other.receive(tasks, options))
+    }
+
+    /// Returns the tasks with the given ids, each once, in the given order.
+    fn transferred<I: IntoIterator<Item = TaskID>>(
+        &self,
+        ids: I,
+    ) -> AnansiResult<Vec<(TaskID, Task)>> {
+        let mut seen = BTreeSet::new();
+        let mut tasks = Vec::new();
+        for id in ids {
+            if !seen.insert(id) {
+                continue;
+            }
+            let Some(task) = self.tasks.get(&id) else {
+                return Err(AnansiError::InvalidID(format!("ID {} does not exist", id)));
+            };
+            tasks.push((id, task.clone()));
+        }
+        Ok(tasks)
+    }
+
+    fn receive(&mut self, tasks: Vec<(TaskID, Task)>, options: &TransferOptions) -> Vec<TaskID> {
+        self.begin_group();
+        let ids = tasks
+            .into_iter()
+            .map(|(_, task)| self.push_task(options.apply(task)))
+            .collect();
+        self.end_group();
+        ids
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Task, util::replace_token};
+
+use super::{Change, List, TaskID};
+
+static UID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const UID_LENGTH: usize = 12;
+const UID_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+impl List {
+    /// Returns `true` if the list gives every task a stable id.
+    pub fn assigns_uids(&self) -> bool {
+        self.assign_uids
+    }
+
+    /// Sets if the list gives every task a stable id, stored in a `uid:` special tag.
+    ///
+    /// Unlike the `TaskID`, which is the position of the task in the file, the uid stays the
+    /// same when the file is edited, sorted or reloaded, so it can be used to link to a task.
+    ///
+    /// When enabled, tasks without a uid (or with one used by another task) get a new one, both
+    /// now and when added later. `update_task` keeps the uid of the task it replaces, whether
+    /// enabled or not.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, Task};
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// list.set_assign_uids(true);
+    /// let id = list.add("(A) Task 1");
+    /// let uid = list.get(id).unwrap().uid().unwrap().to_string();
+    ///
+    /// let done = list.get(id).unwrap().done(None).unwrap();
+    /// list.update_task(done, id).unwrap();
+    /// list.update_task(Task::new("Task 1 renamed", 0), id).unwrap();
+    /// assert_eq!(list.get_by_uid(&uid).unwrap().text(), format!("Task 1 renamed uid:{}", uid));
+    /// ```
+    pub fn set_assign_uids(&mut self, assign: bool) {
+        self.assign_uids = assign;
+        if !assign {
+            return;
+        }
+        let missing: Vec<TaskID> = self
+            .tasks
+            .iter()
+            .filter(|(id, task)| !self.owns_uid(**id, task))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in missing {
+            let before = self.tasks[&id].clone();
+            let after = self.with_uid(before.clone(), id, None);
+            // Not undoable: the uid is part of the identity of the task from now on
+            self.apply_entry(Change::Update { id, before, after });
+        }
+    }
+
+    /// Get a task by its stable id.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// let mut list = List::new("path/to/list.txt");
+    /// let id = list.add("Task 1 uid:abc123");
+    /// assert_eq!(list.get_by_uid("abc123").unwrap().id(), id);
+    /// assert!(list.get_by_uid("def456").is_none());
+    /// ```
+    pub fn get_by_uid(&self, uid: &str) -> Option<&Task> {
+        self.id_by_uid(uid).and_then(|id| self.get(id))
+    }
+
+    /// Returns the id of the task with the given stable id.
+    pub fn id_by_uid(&self, uid: &str) -> Option<TaskID> {
+        self.index.uid(uid)
+    }
+
+    /// Returns `task`, which is to be stored under `id`, with its stable id.
+    ///
+    /// Keeps the uid of `previous`, the task being replaced, and assigns a new one if enabled.
+    pub(super) fn with_uid(&self, mut task: Task, id: TaskID, previous: Option<&Task>) -> Task {
+        if task.uid().is_none()
+            && let Some(uid) = previous.and_then(Task::uid)
+        {
+            task.update_text(format!("{} uid:{}", task.text(), uid));
+        }
+        if self.assign_uids && !self.owns_uid(id, &task) {
+            let uid = self.new_uid();
+            let text = match task.uid() {
+                Some(old) => replace_token(
+                    task.text(),
+                    &format!("uid:{}", old),
+                    &format!("uid:{}", uid),
+                ),
+                None if task.text().is_empty() => format!("uid:{}", uid),
+                None => format!("{} uid:{}", task.text(), uid),
+            };
+            task.update_text(text);
+        }
+        task
+    }
+
+    /// Checks if `task` has a uid no other task than `id` uses.
+    fn owns_uid(&self, id: TaskID, task: &Task) -> bool {
+        task.uid()
+            .is_some_and(|uid| self.index.uid(uid).is_none_or(|owner| owner == id))
+    }
+
+    fn new_uid(&self) -> String {
+        loop {
+            let uid = generate_uid();
+            if self.index.uid(&uid).is_none() {
+                return uid;
+            }
+        }
+    }
+}
+
+// Random enough to not collide between lists and sessions: the standard library seeds
+// `RandomState` randomly per process, mixed with the time and a counter.
+fn generate_uid() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(UID_COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    let mut value = hasher.finish();
+    let mut uid = String::with_capacity(UID_LENGTH);
+    for _ in 0..UID_LENGTH {
+        uid.push(UID_ALPHABET[(value % 36) as usize] as char);
+        value /= 36;
+    }
+    uid
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeSet,
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{Fingerprint, Task, error::AnansiResult, util::decode};
+
+use super::{
+    List,
+    builder::deserialise_list,
+    conflict::{Counterpart, align, trimmed_range},
+};
+
+/// The tasks added, removed and modified between two versions of a list.
+///
+/// Added tasks carry the ids they have in the new version, removed tasks the ids they had in
+/// the old version.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChangeSet {
+    added: Vec<Task>,
+    removed: Vec<Task>,
+    modified: Vec<(Task, Task)>,
+    reloaded: bool,
+}
+
+impl ChangeSet {
+    /// Returns the tasks that were added.
+    pub fn added(&self) -> &[Task] {
+        &self.added
+    }
+    /// Returns the tasks that were removed.
+    pub fn removed(&self) -> &[Task] {
+        &self.removed
+    }
+    /// Returns the modified tasks, as pairs of the old and the new version.
+    pub fn modified(&self) -> &[(Task, Task)] {
+        &self.modified
+    }
+    /// Returns `true` if the list was reloaded to include the changes.
+    pub fn is_reloaded(&self) -> bool {
+        self.reloaded
+    }
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    fn between(old: Vec<Task>, new: &List) -> ChangeSet {
+        let old_lines: Vec<String> = old.iter().map(|task| task.to_string()).collect();
+        let old_lines: Vec<&str> = old_lines.iter().map(String::as_str).collect();
+        let new_tasks: Vec<&Task> = new.ordered().collect();
+        let new_lines: Vec<String> = new_tasks.iter().map(|task| task.to_string()).collect();
+
+        let mut changes = ChangeSet::default();
+        let mut matched = BTreeSet::new();
+        for (task, counterpart) in old.into_iter().zip(align(&old_lines, &new_lines)) {
+            match counterpart {
+                Counterpart::Same(n) => {
+                    matched.insert(n);
+                }
+                Counterpart::Changed(n) => {
+                    matched.insert(n);
+                    changes.modified.push((task, new_tasks[n].clone()));
+                }
+                Counterpart::Removed(_) => changes.removed.push(task),
+            }
+        }
+        changes.added = new_tasks
+            .into_iter()
+            .enumerate()
+            .filter(|(n, _)| !matched.contains(n))
+            .map(|(_, task)| task.clone())
+            .collect();
+        changes
+    }
+}
+
+impl List {
+    /// Replace the tasks of the list with the contents of its file.
+    ///
+    /// Unsaved changes are lost. The tasks get new ids like on `load`.
+    ///
+    /// Returns the changes from the list as it was before to the reloaded list.
+    /// Will error if the file cannot be read, or does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::List;
+    ///
+    /// std::fs::write("reload_doc.txt", "Task 1\nTask 2\n").unwrap();
+    /// let mut list = List::load("reload_doc.txt").unwrap();
+    /// std::fs::write("reload_doc.txt", "Task 1 +done\nTask 2\nTask 3\n").unwrap();
+    ///
+    /// let changes = list.reload().unwrap();
+    /// assert_eq!(changes.added()[0].text(), "Task 3");
+    /// assert_eq!(changes.modified()[0].1.text(), "Task 1 +done");
+    /// assert_eq!(list.task_amount(), 3);
+    /// # std::fs::remove_file("reload_doc.txt").unwrap();
+    /// ```
+    pub fn reload(&mut self) -> AnansiResult<ChangeSet> {
+        let contents = self.read_stored()?;
+        let old: Vec<Task> = self.ordered().cloned().collect();
+        Ok(self.reload_from(old, contents))
+    }
+
+    fn reload_from(&mut self, old: Vec<Task>, raw: Vec<u8>) -> ChangeSet {
+        let mut decoded = decode(&raw);
+        let loaded = deserialise_list(self.file_path.clone(), decoded.text.trim());
+        let mut changes = ChangeSet::between(old, &loaded);
+        changes.reloaded = true;
+        self.adopt(loaded);
+        self.format.update(&mut decoded);
+        let body = trimmed_range(&decoded.text);
+        self.observe(&raw, decoded.text, body);
+        self.dirty_ids.clear();
+        changes
+    }
+}
+
+/// What a `WatchedList` does when its file was changed by someone else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReloadPolicy {
+    /// Reload the list, unless it has unsaved changes.
+    #[default]
+    Auto,
+    /// Only report the changes; use `List::reload` or `List::save` to act on them.
+    Notify,
+}
+
+/// A list that notices when its file is changed by someone else.
+///
+/// Watching works by polling: `poll` compares the size and modification time of the file to
+/// the last known version, and only reads and hashes the file if they differ.
+///
+/// Dereferences to the watched `List`.
+///
+/// # Example
+/// ```
+/// use anansi::{List, ReloadPolicy, WatchedList};
+///
+/// std::fs::write("watched_list_doc.txt", "Task 1\n").unwrap();
+/// let mut watched = WatchedList::new(List::load("watched_list_doc.txt").unwrap(), ReloadPolicy::Auto);
+/// assert!(watched.poll().unwrap().is_none());
+///
+/// std::fs::write("watched_list_doc.txt", "Task 1\nTask 2\n").unwrap();
+/// let changes = watched.poll().unwrap().unwrap();
+/// assert!(changes.is_reloaded());
+/// assert_eq!(changes.added()[0].text(), "Task 2");
+/// assert_eq!(watched.task_amount(), 2);
+/// # std::fs::remove_file("watched_list_doc.txt").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchedList {
+    list: List,
+    policy: ReloadPolicy,
+    compare_contents: bool,
+    last_seen: Option<(PathBuf, u64, Option<SystemTime>)>,
+    // The contents last reported without reloading, so they are reported only once
+    reported: Option<Fingerprint>,
+}
+
+impl Deref for WatchedList {
+    type Target = List;
+
+    fn deref(&self) -> &List {
+        &self.list
+    }
+}
+
+impl DerefMut for WatchedList {
+    fn deref_mut(&mut self) -> &mut List {
+        &mut self.list
+    }
+}
+
+impl WatchedList {
+    /// Watch the file of `list`.
+    ///
+    /// Changes are detected relative to the file as the list last loaded or saved it.
+    pub fn new(list: List, policy: ReloadPolicy) -> WatchedList {
+        WatchedList {
+            list,
+            policy,
+            compare_contents: false,
+            last_seen: None,
+            reported: None,
+        }
+    }
+
+    /// Returns the reload policy.
+    pub fn policy(&self) -> ReloadPolicy {
+        self.policy
+    }
+
+    /// Sets the reload policy.
+    pub fn set_policy(&mut self, policy: ReloadPolicy) {
+        self.policy = policy;
+    }
+
+    /// If set, every `poll` hashes the file, even if size and modification time are unchanged.
+    ///
+    /// Catches changes on file systems with a coarse modification time, at the cost of reading
+    /// the file on every poll.
+    pub fn set_compare_contents(&mut self, compare_contents: bool) {
+        self.compare_contents = compare_contents;
+    }
+
+    /// Returns the watched list.
+    pub fn into_inner(self) -> List {
+        self.list
+    }
+
+    /// Check the file for changes.
+    ///
+    /// Returns `None` if the file is unchanged, or was removed. Otherwise returns the changes
+    /// to the file since the list last loaded or saved it, and reloads the list according to
+    /// the `ReloadPolicy`.
+    ///
+    /// Will error if the file exists but cannot be read.
+    pub fn poll(&mut self) -> AnansiResult<Option<ChangeSet>> {
+        let path = self.list.file_path.clone();
+        let Some((size, modified)) = self.list.stored_version()? else {
+            return Ok(None);
+        };
+        let seen = (path, size, modified);
+        let known = match (&self.last_seen, self.list.fingerprint()) {
+            (Some(last_seen), _) if *last_seen == seen => true,
+            (_, Some(fingerprint)) => {
+                fingerprint.size() == seen.1
+                    && fingerprint.modified().is_some()
+                    && fingerprint.modified() == seen.2
+            }
+            _ => false,
+        };
+        if known && !self.compare_contents {
+            return Ok(None);
+        }
+        self.last_seen = Some(seen);
+
+        let contents = self.list.read_stored()?;
+        let fingerprint = Fingerprint::new(&contents, None);
+        let seen_before = |known: Option<&Fingerprint>| {
+            known.is_some_and(|known| known.same_contents(&fingerprint))
+        };
+        if seen_before(self.list.fingerprint()) || seen_before(self.reported.as_ref()) {
+            return Ok(None);
+        }
+        let old = self.list.snapshot_tasks();
+        if self.policy == ReloadPolicy::Auto && !self.list.has_unsaved_changes() {
+            self.reported = None;
+            return Ok(Some(self.list.reload_from(old, contents)));
+        }
+        self.reported = Some(fingerprint);
+        let new = deserialise_list(self.list.file_path.clone(), decode(&contents).text.trim());
+        Ok(Some(ChangeSet::between(old, &new)))
+    }
+
+    /// Poll the file every `interval` until it changes, or `timeout` has passed.
+    ///
+    /// Returns `None` if the timeout passed without a change.
+    pub fn wait_for_change(
+        &mut self,
+        interval: Duration,
+        timeout: Option<Duration>,
+    ) -> AnansiResult<Option<ChangeSet>> {
+        let started = Instant::now();
+        loop {
+            if let Some(changes) = self.poll()? {
+                return Ok(Some(changes));
+            }
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                return Ok(None);
+            }
+            std::thread::sleep(interval);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    Fingerprint,
+    util::{file_version, modified, write_new},
+};
+
+use super::Storage;
+
+/// Stores a list as a directory of files, one per saved version.
+///
+/// Every write adds a new file named after its version number, `00000000000000000001.txt`
+/// and so on; reads return the newest version. Older versions are kept, up to the limit set
+/// with `with_keep`. Writers sharing the directory never overwrite each other's versions.
+///
+/// # Example
+/// ```
+/// use anansi::{DirectoryStorage, List};
+///
+/// let storage = DirectoryStorage::new("directory_storage_doc");
+/// let mut list = List::from_storage(storage.clone()).unwrap();
+/// list.add("Task 1");
+/// list.save().unwrap();
+/// list.add("Task 2");
+/// list.save().unwrap();
+/// assert_eq!(storage.versions().unwrap().len(), 2);
+/// assert_eq!(List::from_storage(storage).unwrap().task_amount(), 2);
+/// # std::fs::remove_dir_all("directory_storage_doc").unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryStorage {
+    dir: PathBuf,
+    keep: Option<usize>,
+}
+
+impl DirectoryStorage {
+    /// Creates a storage in `dir`, keeping all versions.
+    ///
+    /// The directory is created on the first write.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> DirectoryStorage {
+        DirectoryStorage {
+            dir: dir.into(),
+            keep: None,
+        }
+    }
+
+    /// Keep only the newest `keep` versions; older ones are removed on write.
+    ///
+    /// At least one version is always kept.
+    pub fn with_keep(mut self, keep: usize) -> DirectoryStorage {
+        self.keep = Some(keep.max(1));
+        self
+    }
+
+    /// Returns the directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the paths of the stored versions, oldest first.
+    ///
+    /// Will error if the directory exists but cannot be read.
+    pub fn versions(&self) -> io::Result<Vec<PathBuf>> {
+        Ok(self.numbered()?.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn numbered(&self) -> io::Result<Vec<(u64, PathBuf)>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut versions: Vec<(u64, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let version = name.to_str()?.strip_suffix(".txt")?;
+                if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                Some((version.parse().ok()?, entry.path()))
+            })
+            .collect();
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Removes the oldest versions so `keep` are left, but none newer than `written`, which
+    /// other writers may have just added.
+    fn prune(&self, written: u64, keep: usize) -> io::Result<()> {
+        let versions = self.numbered()?;
+        let surplus = versions.len().saturating_sub(keep);
+        for (version, path) in versions.into_iter().take(surplus) {
+            if version >= written {
+                break;
+            }
+            match std::fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn latest(&self) -> io::Result<Option<PathBuf>> {
+        Ok(self.numbered()?.pop().map(|(_, path)| path))
+    }
+}
+
+impl Storage for DirectoryStorage {
+    fn read(&self) -> io::Result<Vec<u8>> {
+        match self.latest()? {
+            Some(path) => std::fs::read(path),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn write(&self, contents: &[u8]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // Another writer may take the same version number first, then the next one is tried
+        let written = loop {
+            let next = self
+                .numbered()?
+                .last()
+                .map_or(1, |(version, _)| version + 1);
+            let path = self.dir.join(format!("{:020}.txt", next));
+            match write_new(&path, contents) {
+                Ok(()) => break next,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        };
+        if let Some(keep) = self.keep {
+            self.prune(written, keep)?;
+        }
+        Ok(())
+    }
+
+    fn fingerprint(&self) -> io::Result<Option<Fingerprint>> {
+        match self.latest()? {
+            Some(path) => {
+                let contents = std::fs::read(&path)?;
+                Ok(Some(Fingerprint::new(&contents, modified(&path))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn version(&self) -> io::Result<Option<(u64, Option<SystemTime>)>> {
+        match self.latest()? {
+            Some(path) => file_version(path),
+            None => Ok(None),
+        }
+    }
+}
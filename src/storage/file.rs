@@ -0,0 +1,68 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    Fingerprint,
+    util::{file_version, modified, write_atomic},
+};
+
+use super::Storage;
+
+/// Stores a list in a single file; what `List::load` and `List::save` use.
+///
+/// Writes replace the file atomically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    /// Creates a storage for the file at `path`. The file does not have to exist.
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileStorage {
+        FileStorage { path: path.into() }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&self) -> io::Result<Vec<u8>> {
+        std::fs::read(&self.path)
+    }
+
+    fn write(&self, contents: &[u8]) -> io::Result<()> {
+        write_atomic(&self.path, contents)
+    }
+
+    fn fingerprint(&self) -> io::Result<Option<Fingerprint>> {
+        match std::fs::read(&self.path) {
+            Ok(contents) => Ok(Some(Fingerprint::new(&contents, modified(&self.path)))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn version(&self) -> io::Result<Option<(u64, Option<SystemTime>)>> {
+        file_version(&self.path)
+    }
+
+    fn supports_append(&self) -> bool {
+        true
+    }
+
+    fn append(&self, contents: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+}
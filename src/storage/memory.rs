@@ -0,0 +1,108 @@
+use std::{
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use crate::Fingerprint;
+
+use super::Storage;
+
+/// Stores a list in memory; useful for tests.
+///
+/// Clones share the same contents, so a clone can be kept to inspect what a list saved.
+///
+/// # Example
+/// ```
+/// use anansi::{List, MemoryStorage};
+///
+/// let storage = MemoryStorage::new();
+/// let mut list = List::from_storage(storage.clone()).unwrap();
+/// list.add("Task 1");
+/// list.save().unwrap();
+/// assert_eq!(storage.contents().unwrap(), b"Task 1\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    stored: Arc<Mutex<Option<Stored>>>,
+}
+
+#[derive(Debug)]
+struct Stored {
+    contents: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl MemoryStorage {
+    /// Creates an empty storage.
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    /// Creates a storage holding `contents`.
+    pub fn with_contents<B: Into<Vec<u8>>>(contents: B) -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        storage.set_contents(contents);
+        storage
+    }
+
+    /// Returns the stored contents, or `None` if nothing was stored yet.
+    pub fn contents(&self) -> Option<Vec<u8>> {
+        self.stored().as_ref().map(|stored| stored.contents.clone())
+    }
+
+    /// Replaces the stored contents, as if someone else saved the list.
+    pub fn set_contents<B: Into<Vec<u8>>>(&self, contents: B) {
+        *self.stored() = Some(Stored {
+            contents: contents.into(),
+            modified: SystemTime::now(),
+        });
+    }
+
+    fn stored(&self) -> MutexGuard<'_, Option<Stored>> {
+        // The contents are always valid, even if another thread panicked while holding the lock
+        self.stored
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self) -> io::Result<Vec<u8>> {
+        self.contents()
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn write(&self, contents: &[u8]) -> io::Result<()> {
+        self.set_contents(contents);
+        Ok(())
+    }
+
+    fn fingerprint(&self) -> io::Result<Option<Fingerprint>> {
+        Ok(self
+            .stored()
+            .as_ref()
+            .map(|stored| Fingerprint::new(&stored.contents, Some(stored.modified))))
+    }
+
+    fn supports_append(&self) -> bool {
+        true
+    }
+
+    fn append(&self, contents: &[u8]) -> io::Result<()> {
+        let mut stored = self.stored();
+        match stored.as_mut() {
+            Some(stored) => {
+                stored.contents.extend_from_slice(contents);
+                stored.modified = SystemTime::now();
+            }
+            None => {
+                *stored = Some(Stored {
+                    contents: contents.to_vec(),
+                    modified: SystemTime::now(),
+                })
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+mod directory;
+mod file;
+mod memory;
+mod test;
+
+use std::{fmt::Debug, io, time::SystemTime};
+
+pub use directory::DirectoryStorage;
+pub use file::FileStorage;
+pub use memory::MemoryStorage;
+
+use crate::Fingerprint;
+
+/// Where a list is persisted.
+///
+/// A storage holds one snapshot of a list: the serialised tasks, one per line. `List::from_storage`
+/// loads a list from a storage, and `List::save` writes it back.
+///
+/// Storages are shared between threads and lists, so they take `&self`; implementations
+/// with mutable state need interior mutability.
+///
+/// # Example
+/// ```
+/// use anansi::{List, MemoryStorage, Storage};
+///
+/// let storage = MemoryStorage::with_contents("Task 1\n");
+/// let mut list = List::from_storage(storage.clone()).unwrap();
+/// list.add("Task 2");
+/// list.save().unwrap();
+/// assert_eq!(storage.read().unwrap(), b"Task 1\nTask 2\n");
+/// ```
+pub trait Storage: Debug + Send + Sync {
+    /// Returns the stored snapshot.
+    ///
+    /// Will error with `std::io::ErrorKind::NotFound` if nothing was stored yet.
+    fn read(&self) -> io::Result<Vec<u8>>;
+
+    /// Replaces the stored snapshot with `contents`.
+    ///
+    /// Readers must see either the old or the new snapshot, never a partial write.
+    fn write(&self, contents: &[u8]) -> io::Result<()>;
+
+    /// Returns the fingerprint of the stored snapshot, or `None` if nothing was stored yet.
+    fn fingerprint(&self) -> io::Result<Option<Fingerprint>>;
+
+    /// Returns the size and modification time of the stored snapshot, or `None` if nothing was
+    /// stored yet.
+    ///
+    /// Used to check for changes cheaply, so implementations should avoid reading the
+    /// snapshot. Defaults to the size and modification time of the `fingerprint`.
+    fn version(&self) -> io::Result<Option<(u64, Option<SystemTime>)>> {
+        Ok(self
+            .fingerprint()?
+            .map(|fingerprint| (fingerprint.size(), fingerprint.modified())))
+    }
+
+    /// Returns `true` if the storage supports `append`.
+    fn supports_append(&self) -> bool {
+        false
+    }
+
+    /// Appends `contents` to the stored snapshot.
+    ///
+    /// Will error with `std::io::ErrorKind::Unsupported` unless `supports_append` is `true`.
+    fn append(&self, contents: &[u8]) -> io::Result<()> {
+        let _ = contents;
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod storages {
+    use crate::{DirectoryStorage, FileStorage, MemoryStorage, Storage};
+
+    fn check_storage(storage: &dyn Storage) {
+        assert_eq!(
+            storage.read().unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert_eq!(storage.fingerprint().unwrap(), None);
+
+        storage.write(b"Task 1\n").unwrap();
+        assert_eq!(storage.read().unwrap(), b"Task 1\n");
+        let first = storage.fingerprint().unwrap().unwrap();
+
+        storage.write(b"Task 2\n").unwrap();
+        assert_eq!(storage.read().unwrap(), b"Task 2\n");
+        let second = storage.fingerprint().unwrap().unwrap();
+        assert!(!first.same_contents(&second));
+        assert_eq!(
+            storage.version().unwrap(),
+            Some((second.size(), second.modified()))
+        );
+
+        if storage.supports_append() {
+            storage.append(b"Task 3\n").unwrap();
+            assert_eq!(storage.read().unwrap(), b"Task 2\nTask 3\n");
+        } else {
+            assert!(storage.append(b"Task 3\n").is_err());
+        }
+    }
+
+    #[test]
+    fn memory_storage() {
+        check_storage(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn file_storage() {
+        let path = "test-data/file-storage-test.txt";
+        let _ = std::fs::remove_file(path);
+        check_storage(&FileStorage::new(path));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn directory_storage() {
+        let dir = "test-data/directory-storage-test";
+        let _ = std::fs::remove_dir_all(dir);
+        check_storage(&DirectoryStorage::new(dir));
+
+        let storage = DirectoryStorage::new(dir).with_keep(2);
+        storage.write(b"Task 4\n").unwrap();
+        assert_eq!(storage.versions().unwrap().len(), 2);
+        assert_eq!(storage.read().unwrap(), b"Task 4\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_directory_writes_keep_every_version() {
+        let dir = "test-data/directory-storage-concurrent";
+        let _ = std::fs::remove_dir_all(dir);
+        let storage = DirectoryStorage::new(dir);
+        std::thread::scope(|scope| {
+            for writer in 0..4 {
+                let storage = &storage;
+                scope.spawn(move || {
+                    for n in 0..10 {
+                        storage
+                            .write(format!("Task {} {}\n", writer, n).as_bytes())
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        let versions = storage.versions().unwrap();
+        assert_eq!(versions.len(), 40);
+        let mut written: Vec<String> = versions
+            .iter()
+            .map(|path| std::fs::read_to_string(path).unwrap())
+            .collect();
+        written.sort();
+        written.dedup();
+        assert_eq!(written.len(), 40);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
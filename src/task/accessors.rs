@@ -33,7 +33,7 @@ impl Task {
     /// assert_eq!(task.contexts(), &vec!["air", "home"]);
     /// ```
     pub fn contexts(&self) -> &Vec<String> {
-        &self.context_tags
+        self.context_tags.as_vec(self.text())
     }
     /// Returns all project tags of the task.
     ///
@@ -46,7 +46,7 @@ impl Task {
     /// assert_eq!(task.projects(), &vec!["air", "home"]);
     /// ```
     pub fn projects(&self) -> &Vec<String> {
-        &self.project_tags
+        self.project_tags.as_vec(self.text())
     }
     /// Returns all special tags of the task as a map.
     ///
@@ -59,7 +59,21 @@ impl Task {
     /// assert_eq!(task.specials().get("due").unwrap(), "tomorrow");
     /// ```
     pub fn specials(&self) -> &BTreeMap<String, String> {
-        &self.special_tags
+        self.special_tags.as_map(self.text())
+    }
+    /// Returns the stable id of the task, stored in its `uid:` special tag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anansi::Task;
+    ///
+    /// let task = anansi::Task::new("(A) test uid:k3f9x2", 0);
+    /// assert_eq!(task.uid(), Some("k3f9x2"));
+    /// assert_eq!(anansi::Task::new("(A) test", 0).uid(), None);
+    /// ```
+    pub fn uid(&self) -> Option<&str> {
+        self.special("uid")
     }
     /// Returns the completion date of the task.
     ///
@@ -103,6 +117,28 @@ impl Task {
     /// assert_eq!(task.text(), "test +proj @cont key:val");
     /// ```
     pub fn text(&self) -> &str {
-        &self.text
+        &self.buffer[self.text_start as usize..]
+    }
+
+    // Tags without turning them into strings, for lists that go through the tags of many tasks
+
+    /// Iterates over the context tags.
+    pub(crate) fn iter_contexts(&self) -> impl Iterator<Item = &str> {
+        self.context_tags.iter(self.text())
+    }
+
+    /// Iterates over the project tags.
+    pub(crate) fn iter_projects(&self) -> impl Iterator<Item = &str> {
+        self.project_tags.iter(self.text())
+    }
+
+    /// Iterates over the special tags as key and value. A key may repeat, the last one counts.
+    pub(crate) fn iter_specials(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.special_tags.iter(self.text())
+    }
+
+    /// Returns the value of the special tag `key`.
+    pub(crate) fn special(&self, key: &str) -> Option<&str> {
+        self.special_tags.get(key, self.text())
     }
 }
@@ -1,11 +1,13 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::VecDeque;
 
 use crate::{Date, util::deserialise_date};
 
-use super::Task;
+use super::{
+    Task,
+    span::{Span, SpecialList, TagList},
+};
 
 pub fn deserialize_task<S: AsRef<str>>(input: S, id: usize) -> Task {
-    let original_text = input.as_ref().to_string();
     let mut tokens: VecDeque<&str> = input.as_ref().split_whitespace().collect();
 
     let done = if tokens[0] == "x" { true } else { false };
@@ -57,45 +59,44 @@ pub fn deserialize_task<S: AsRef<str>>(input: S, id: usize) -> Task {
     }
 
     let mut text = String::new();
-    let mut context_tags: Vec<String> = Vec::new();
-    let mut project_tags: Vec<String> = Vec::new();
-    let mut special_tags: BTreeMap<String, String> = BTreeMap::new();
+    let mut context_tags: Vec<Span> = Vec::new();
+    let mut project_tags: Vec<Span> = Vec::new();
+    let mut special_tags: Vec<(Span, Span)> = Vec::new();
     // This also removes all newline characters
     for token in tokens {
+        let start = text.len();
+        let end = start + token.len();
         if token.starts_with('@') && token.len() > 1 {
-            text.push_str(token);
-            text.push(' ');
-            context_tags.push(token.strip_prefix('@').unwrap().to_string());
+            context_tags.push(Span::new(start + 1..end));
         } else if token.starts_with('+') && token.len() > 1 {
-            text.push_str(token);
-            text.push(' ');
-            project_tags.push(token.strip_prefix('+').unwrap().to_string());
+            project_tags.push(Span::new(start + 1..end));
         } else if token.len() > 2
             && !token.starts_with(':')
             && !token.ends_with(':')
             && token.contains(':')
             && !token.contains("::")
         {
-            text.push_str(token);
-            text.push(' ');
-            let (key, value) = token.split_once(':').unwrap();
-            special_tags.insert(key.to_string(), value.to_string());
-        } else {
-            text.push_str(token);
-            text.push(' ');
+            let colon = start + token.find(':').unwrap();
+            special_tags.push((Span::new(start..colon), Span::new(colon + 1..end)));
         }
+        text.push_str(token);
+        text.push(' ');
     }
+    text.truncate(text.trim_end().len());
 
-    Task {
+    let mut task = Task {
         id,
         done,
         priority,
         completion_date,
         inception_date,
-        text: text.trim_end().to_string(),
-        context_tags,
-        project_tags,
-        special_tags,
-        original_text,
-    }
+        buffer: Box::default(),
+        line_len: 0,
+        text_start: 0,
+        context_tags: TagList::from_spans(context_tags),
+        project_tags: TagList::from_spans(project_tags),
+        special_tags: SpecialList::from_spans(special_tags),
+    };
+    task.set_line(input.as_ref().to_string(), &text);
+    task
 }
@@ -1,11 +1,11 @@
 mod accessors;
 mod builder;
 mod mutators;
+mod span;
 mod test;
 
-use std::collections::BTreeMap;
-
 use builder::deserialize_task;
+use span::{SpecialList, TagList};
 
 use crate::Date;
 
@@ -56,22 +56,27 @@ pub struct Task {
     priority: Option<char>,
     completion_date: Date,
     inception_date: Date,
-    // The text of the task, with the tags but without the head (prio, dates, done)
-    text: String,
-    context_tags: Vec<String>,
-    project_tags: Vec<String>,
-    special_tags: BTreeMap<String, String>,
-    // complete text (including `x` dates etc.)
-    original_text: String,
+    // The complete line of the task (including `x` dates etc.), followed by the text of the
+    // task unless the line already ends with it
+    buffer: Box<str>,
+    // Length of the line at the start of `buffer`
+    line_len: u32,
+    // Start of the text in `buffer`, which runs to its end. The text has the tags, but not
+    // the head (prio, dates, done)
+    text_start: u32,
+    // Spans of the tags are relative to the text
+    context_tags: TagList,
+    project_tags: TagList,
+    special_tags: SpecialList,
 }
 
 impl PartialEq for Task {
     fn eq(&self, other: &Self) -> bool {
-        self.original_text == other.original_text
+        self.line() == other.line()
     }
 
     fn ne(&self, other: &Self) -> bool {
-        self.original_text != other.original_text
+        self.line() != other.line()
     }
 }
 
@@ -131,11 +136,12 @@ impl Task {
             priority: None,
             completion_date: Date::default(),
             inception_date: Date::default(),
-            text: "".to_string(),
-            context_tags: vec![],
-            project_tags: vec![],
-            special_tags: BTreeMap::new(),
-            original_text: "".to_string(),
+            buffer: Box::default(),
+            line_len: 0,
+            text_start: 0,
+            context_tags: TagList::default(),
+            project_tags: TagList::default(),
+            special_tags: SpecialList::default(),
         }
     }
 
@@ -148,13 +154,29 @@ impl Task {
         self.id = id;
         self
     }
-}
 
-// ---------------------------------------------------------------
-//                        Display implementation
-// ---------------------------------------------------------------
-impl std::fmt::Display for Task {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Returns the complete line of the task, as it was read or last set.
+    fn line(&self) -> &str {
+        &self.buffer[..self.line_len as usize]
+    }
+
+    /// Replaces the line and the text of the task, keeping the tags.
+    fn set_line(&mut self, line: String, text: &str) {
+        let mut buffer = line;
+        let line_len = buffer.len();
+        let text_start = if buffer.ends_with(text) {
+            line_len - text.len()
+        } else {
+            buffer.push_str(text);
+            line_len
+        };
+        self.buffer = buffer.into_boxed_str();
+        self.line_len = line_len as u32;
+        self.text_start = text_start as u32;
+    }
+
+    /// Returns the head of the line as it is written: done, priority and dates.
+    fn head(&self) -> String {
         let mut string = String::new();
         if self.done {
             string.push('x');
@@ -174,8 +196,16 @@ impl std::fmt::Display for Task {
             string.push_str(&self.inception_date());
             string.push(' ');
         }
-        string.push_str(&self.text);
-        write!(f, "{}", string)
+        string
+    }
+}
+
+// ---------------------------------------------------------------
+//                        Display implementation
+// ---------------------------------------------------------------
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.head(), self.text())
     }
 }
 
@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use crate::{AnansiError, Date, Task};
 
+use super::span::{SpecialList, TagList};
+
 impl Task {
     /// Updates the id of the task.
     ///
@@ -38,7 +40,7 @@ impl Task {
     /// assert_eq!(task.contexts().len(), 1);
     /// ```
     pub fn update_context_tags(&mut self, new_context: Vec<String>) {
-        self.context_tags = new_context;
+        self.context_tags = TagList::from_strings(new_context);
     }
     /// Updates the project tags of the task.
     ///
@@ -54,7 +56,7 @@ impl Task {
     /// assert_eq!(task.projects().len(), 1);
     /// ```
     pub fn update_project_tags(&mut self, new_project: Vec<String>) {
-        self.project_tags = new_project;
+        self.project_tags = TagList::from_strings(new_project);
     }
     /// Updates the special tags of the task.
     ///
@@ -70,7 +72,7 @@ impl Task {
     /// assert_eq!(task.specials().len(), 1);
     /// ```
     pub fn update_special_tags(&mut self, new_special: BTreeMap<String, String>) {
-        self.special_tags = new_special;
+        self.special_tags = SpecialList::from_map(new_special);
     }
     /// Adds a context tag.
     ///
@@ -84,7 +86,8 @@ impl Task {
     /// assert_eq!(task.contexts().len(), 1);
     /// ```
     pub fn add_context_tag(&mut self, new_context: String) {
-        self.context_tags.push(new_context);
+        let text = &self.buffer[self.text_start as usize..];
+        self.context_tags.to_mut(text).push(new_context);
     }
     /// Adds a project tag.
     ///
@@ -98,7 +101,8 @@ impl Task {
     /// assert_eq!(task.projects().len(), 1);
     /// ```
     pub fn add_project_tag(&mut self, new_project: String) {
-        self.project_tags.push(new_project);
+        let text = &self.buffer[self.text_start as usize..];
+        self.project_tags.to_mut(text).push(new_project);
     }
     /// Adds a special tag.
     ///
@@ -112,7 +116,8 @@ impl Task {
     /// assert_eq!(task.specials().len(), 1);
     /// ```
     pub fn add_special_tag(&mut self, key: String, value: String) {
-        self.special_tags.insert(key, value);
+        let text = &self.buffer[self.text_start as usize..];
+        self.special_tags.to_mut(text).insert(key, value);
     }
     /// Removes a context tag.
     ///
@@ -128,7 +133,8 @@ impl Task {
     /// assert_eq!(task.contexts().len(), 0);
     /// ```
     pub fn remove_context_tag(&mut self, tag: String) {
-        self.context_tags.retain(|t| t != &tag);
+        let text = &self.buffer[self.text_start as usize..];
+        self.context_tags.to_mut(text).retain(|t| t != &tag);
     }
     /// Removes a project tag.
     ///
@@ -144,7 +150,8 @@ impl Task {
     /// assert_eq!(task.projects().len(), 0);
     /// ```
     pub fn remove_project_tag(&mut self, tag: String) {
-        self.project_tags.retain(|t| t != &tag);
+        let text = &self.buffer[self.text_start as usize..];
+        self.project_tags.to_mut(text).retain(|t| t != &tag);
     }
     /// Removes a special tag by key.
     ///
@@ -160,7 +167,8 @@ impl Task {
     /// assert_eq!(task.specials().len(), 0);
     /// ```
     pub fn remove_special_tag(&mut self, key: String) {
-        self.special_tags.remove(&key);
+        let text = &self.buffer[self.text_start as usize..];
+        self.special_tags.to_mut(text).remove(&key);
     }
     /// Updates the completion date of the task.
     ///
@@ -221,12 +229,19 @@ impl Task {
     /// ```
     pub fn update_text<S: Into<String>>(&mut self, new_text: S) {
         let new_text = new_text.into();
-        self.text = new_text.clone();
-        let new_task = Task::from((self.to_string().as_str(), 0usize));
-        self.original_text = new_task.original_text;
+        let line = format!("{}{}", self.head(), new_text);
+        let mut new_task = Task::from((line.as_str(), 0usize));
+        // The tags point into the parsed text, which only differs in whitespace
+        if new_task.text() != new_text {
+            let text = &new_task.buffer[new_task.text_start as usize..];
+            new_task.context_tags.to_mut(text);
+            new_task.project_tags.to_mut(text);
+            new_task.special_tags.to_mut(text);
+        }
         self.context_tags = new_task.context_tags;
         self.project_tags = new_task.project_tags;
         self.special_tags = new_task.special_tags;
+        self.set_line(line, &new_text);
     }
     /// Marks the task as done.
     ///
@@ -270,19 +285,23 @@ impl Task {
             task.done = true;
             if task.inception_date.is_set() {
                 if let Some(date) = completion_date {
-                    task.original_text = format!(
+                    let line = format!(
                         "x ({}) {} {} {}",
                         task.prio().unwrap_or(' '),
                         date,
                         task.inception_date,
-                        task.text
+                        task.text()
                     );
+                    let text = task.text().to_string();
+                    task.set_line(line, &text);
                     task.completion_date = date;
                 } else {
                     return Err(AnansiError::MissingCompletionDate(self.id));
                 }
             } else {
-                task.original_text = format!("x ({}) {}", task.prio().unwrap_or(' '), task.text);
+                let line = format!("x ({}) {}", task.prio().unwrap_or(' '), task.text());
+                let text = task.text().to_string();
+                task.set_line(line, &text);
             }
         }
         Ok(task)
@@ -314,14 +333,18 @@ impl Task {
             task.done = false;
             if task.completion_date.is_set() {
                 task.completion_date = Date::default();
-                task.original_text = format!(
+                let line = format!(
                     "({}) {} {}",
                     task.prio().unwrap_or(' '),
                     task.inception_date,
-                    task.text
+                    task.text()
                 );
+                let text = task.text().to_string();
+                task.set_line(line, &text);
             } else {
-                task.original_text = task.original_text.replacen("x ", "", 1);
+                let line = task.line().replacen("x ", "", 1);
+                let text = task.text().to_string();
+                task.set_line(line, &text);
             }
         }
         task
@@ -0,0 +1,147 @@
+// Tags are kept as byte ranges into the text of their task instead of strings of their own,
+// so a task holds a single allocation for its text no matter how many tags it has.
+// They are only turned into strings when borrowed as such, or when changed.
+//
+// Tag names are not interned across a list. Lists go through the spans, so besides the text
+// of the tasks the only strings of a tag name in a list are the keys of its index, one per
+// distinct tag.
+
+use std::{collections::BTreeMap, ops::Range, sync::OnceLock};
+
+/// A range of bytes in the text of a task.
+///
+/// Offsets are stored as `u32` to keep tags small, which limits a task to 4 GiB of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    pub(super) fn new(range: Range<usize>) -> Span {
+        let offset = |n: usize| u32::try_from(n).expect("task text is longer than 4 GiB");
+        Span {
+            start: offset(range.start),
+            end: offset(range.end),
+        }
+    }
+
+    fn get<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start as usize..self.end as usize]
+    }
+}
+
+/// Context or project tags of a task, without their `@` or `+`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TagList {
+    spans: Box<[Span]>,
+    // Once set, holds all tags and the spans are ignored
+    strings: OnceLock<Vec<String>>,
+}
+
+impl TagList {
+    pub(super) fn from_spans(spans: Vec<Span>) -> TagList {
+        TagList {
+            spans: spans.into_boxed_slice(),
+            strings: OnceLock::new(),
+        }
+    }
+
+    pub(super) fn from_strings(strings: Vec<String>) -> TagList {
+        TagList {
+            spans: Box::default(),
+            strings: OnceLock::from(strings),
+        }
+    }
+
+    pub(super) fn iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> {
+        let (strings, spans) = match self.strings.get() {
+            Some(strings) => (strings.as_slice(), &[][..]),
+            None => (&[][..], &self.spans[..]),
+        };
+        let strings = strings.iter().map(String::as_str);
+        strings.chain(spans.iter().map(|span| span.get(text)))
+    }
+
+    pub(super) fn as_vec(&self, text: &str) -> &Vec<String> {
+        self.strings
+            .get_or_init(|| self.iter(text).map(str::to_string).collect())
+    }
+
+    pub(super) fn to_mut(&mut self, text: &str) -> &mut Vec<String> {
+        self.as_vec(text);
+        self.spans = Box::default();
+        self.strings
+            .get_mut()
+            .expect("tags were just turned into strings")
+    }
+}
+
+/// Special tags of a task as key and value; a later tag with the same key wins.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SpecialList {
+    spans: Box<[(Span, Span)]>,
+    // Once set, holds all tags and the spans are ignored
+    map: OnceLock<BTreeMap<String, String>>,
+}
+
+impl SpecialList {
+    pub(super) fn from_spans(spans: Vec<(Span, Span)>) -> SpecialList {
+        SpecialList {
+            spans: spans.into_boxed_slice(),
+            map: OnceLock::new(),
+        }
+    }
+
+    pub(super) fn from_map(map: BTreeMap<String, String>) -> SpecialList {
+        SpecialList {
+            spans: Box::default(),
+            map: OnceLock::from(map),
+        }
+    }
+
+    pub(super) fn get<'a>(&'a self, key: &str, text: &'a str) -> Option<&'a str> {
+        match self.map.get() {
+            Some(map) => map.get(key).map(String::as_str),
+            None => self
+                .spans
+                .iter()
+                .rev()
+                .find(|(k, _)| k.get(text) == key)
+                .map(|(_, value)| value.get(text)),
+        }
+    }
+
+    /// Iterates over all tags; keys may repeat unless the tags were turned into a map.
+    pub(super) fn iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let (map, spans) = match self.map.get() {
+            Some(map) => (Some(map), &[][..]),
+            None => (None, &self.spans[..]),
+        };
+        let map = map
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.as_str(), value.as_str()));
+        map.chain(
+            spans
+                .iter()
+                .map(|(key, value)| (key.get(text), value.get(text))),
+        )
+    }
+
+    pub(super) fn as_map(&self, text: &str) -> &BTreeMap<String, String> {
+        self.map.get_or_init(|| {
+            self.iter(text)
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        })
+    }
+
+    pub(super) fn to_mut(&mut self, text: &str) -> &mut BTreeMap<String, String> {
+        self.as_map(text);
+        self.spans = Box::default();
+        self.map
+            .get_mut()
+            .expect("tags were just turned into a map")
+    }
+}
@@ -271,3 +271,42 @@ fn priorities() {
         assert_eq!(task.prio(), Some(letter.chars().next().unwrap()));
     }
 }
+
+#[test]
+fn tags_point_into_the_text() {
+    // The text is normalised, so it is stored next to the line
+    let mut task = Task::new(
+        "x  2010-10-20 2010-10-01 a  @home +garden due:soon due:later",
+        0,
+    );
+    assert_eq!(task.text(), "a @home +garden due:soon due:later");
+    assert_eq!(task.iter_contexts().collect::<Vec<_>>(), vec!["home"]);
+    assert_eq!(task.special("due"), Some("later"));
+    assert_eq!(task.specials().get("due").unwrap(), "later");
+    assert_ne!(
+        task,
+        Task::new(
+            "x 2010-10-20 2010-10-01 a @home +garden due:soon due:later",
+            0
+        )
+    );
+
+    task.add_context_tag("work".into());
+    task.remove_project_tag("garden".into());
+    assert_eq!(
+        task.contexts(),
+        &vec!["home".to_string(), "work".to_string()]
+    );
+    assert_eq!(task.iter_projects().count(), 0);
+    assert_eq!(task.text(), "a @home +garden due:soon due:later");
+
+    // Tags are parsed from the new text, which keeps its whitespace
+    task.update_text("b  @office due:now");
+    assert_eq!(task.text(), "b  @office due:now");
+    assert_eq!(task.iter_contexts().collect::<Vec<_>>(), vec!["office"]);
+    assert_eq!(task.special("due"), Some("now"));
+
+    let undone = task.undone();
+    assert_eq!(undone.to_string(), "2010-10-01 b  @office due:now");
+    assert_eq!(undone.contexts(), task.contexts());
+}
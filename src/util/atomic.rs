@@ -0,0 +1,91 @@
+use std::{
+    fs::{OpenOptions, Permissions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the contents of the file at `path` with `contents` atomically.
+///
+/// The data is written to a temporary file in the same directory, flushed to disk and then
+/// renamed over `path`. A crash or full disk leaves either the old or the new file behind,
+/// never a truncated one.
+///
+/// If `path` is a symlink, the file it points to is replaced and the link is kept. The new
+/// file gets the permissions of the one it replaces.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> std::io::Result<()> {
+    let path = match std::fs::canonicalize(path.as_ref()) {
+        Ok(target) => target,
+        Err(_) => path.as_ref().to_path_buf(),
+    };
+    let permissions = std::fs::metadata(&path).ok().map(|meta| meta.permissions());
+    let temp_path = temp_path(&path);
+    let written = write_and_sync(&temp_path, contents, permissions)
+        .and_then(|_| std::fs::rename(&temp_path, &path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written?;
+    sync_dir(&path);
+    Ok(())
+}
+
+/// Creates the file at `path` with `contents` atomically, failing with
+/// `std::io::ErrorKind::AlreadyExists` if it exists.
+///
+/// Like `write_atomic`, the data is written to a temporary file first; it is then hard linked
+/// to `path`, which never replaces an existing file.
+pub fn write_new<P: AsRef<Path>>(path: P, contents: &[u8]) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let temp_path = temp_path(path);
+    let written = write_and_sync(&temp_path, contents, None)
+        .and_then(|_| std::fs::hard_link(&temp_path, path));
+    let _ = std::fs::remove_file(&temp_path);
+    written?;
+    sync_dir(path);
+    Ok(())
+}
+
+fn write_and_sync(
+    path: &Path,
+    contents: &[u8],
+    permissions: Option<Permissions>,
+) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions)?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.tmp-{}-{}", name, std::process::id(), count))
+}
+
+/// Returns the directory containing `path`, treating a bare file name as the current directory.
+pub fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+// Makes the rename itself durable. Only possible (and needed) on unix; failures are ignored as
+// the file contents are already safely on disk at this point.
+#[cfg(unix)]
+fn sync_dir(path: &Path) {
+    if let Ok(dir) = std::fs::File::open(parent_dir(path)) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) {}
@@ -12,7 +12,7 @@ use super::deserialise_date;
 /// Consider using the `Date::from` function instead. It takes any string (correctly formatted) as an argument.
 ///
 /// Date considers the date '0000-00-00' to be invalid.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Date {
     year: u16,
     month: u8,
@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+
+/// The UTF-8 byte order mark.
+pub const BOM: &str = "\u{FEFF}";
+
+/// The line ending written between tasks when a list is saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as used on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line ending as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Returns the line ending used by most lines of `text`, or `None` if it has no line breaks.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::detect("Task 1\r\nTask 2\r\n"), Some(LineEnding::CrLf));
+    /// assert_eq!(LineEnding::detect("Task 1\nTask 2\r\nTask 3\n"), Some(LineEnding::Lf));
+    /// assert_eq!(LineEnding::detect("Task 1"), None);
+    /// ```
+    pub fn detect(text: &str) -> Option<LineEnding> {
+        LineEnding::from_counts(text.matches("\r\n").count(), text.matches('\n').count())
+    }
+
+    fn from_counts(crlf: usize, lf: usize) -> Option<LineEnding> {
+        match lf {
+            0 => None,
+            _ if crlf * 2 > lf => Some(LineEnding::CrLf),
+            _ => Some(LineEnding::Lf),
+        }
+    }
+}
+
+/// A line of a list file that is not valid UTF-8.
+///
+/// The line was loaded with the invalid bytes replaced by `U+FFFD`; saving the list writes
+/// the replaced text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeIssue {
+    line: usize,
+    bytes: Vec<u8>,
+}
+
+impl DecodeIssue {
+    /// Returns the number of the line in the file, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the original bytes of the line, without the line ending.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The text of a list file and how it was encoded.
+#[derive(Debug, Clone, Default)]
+pub struct Decoded {
+    pub text: String,
+    pub bom: bool,
+    pub line_ending: Option<LineEnding>,
+    pub issues: Vec<DecodeIssue>,
+}
+
+/// Decodes the contents of a list file, replacing invalid UTF-8 line by line.
+pub fn decode(bytes: &[u8]) -> Decoded {
+    let (bom, bytes) = match bytes.strip_prefix(BOM.as_bytes()) {
+        Some(bytes) => (true, bytes),
+        None => (false, bytes),
+    };
+    let mut issues = Vec::new();
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => {
+            let lines: Vec<Cow<str>> = bytes
+                .split(|byte| *byte == b'\n')
+                .enumerate()
+                .map(|(n, line)| decode_line(line, n + 1, &mut issues))
+                .collect();
+            lines.join("\n")
+        }
+    };
+    Decoded {
+        line_ending: LineEnding::detect(&text),
+        text,
+        bom,
+        issues,
+    }
+}
+
+/// Decodes line number `line` of a file, recording an issue if it is not valid UTF-8.
+pub fn decode_line<'a>(
+    bytes: &'a [u8],
+    line: usize,
+    issues: &mut Vec<DecodeIssue>,
+) -> Cow<'a, str> {
+    let text = String::from_utf8_lossy(bytes);
+    if let Cow::Owned(_) = text {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        issues.push(DecodeIssue {
+            line,
+            bytes: bytes.to_vec(),
+        });
+    }
+    text
+}
+
+/// Strips a UTF-8 byte order mark from the start of `bytes`. Returns `true` if there was one.
+pub fn strip_bom(bytes: &mut Vec<u8>) -> bool {
+    if bytes.starts_with(BOM.as_bytes()) {
+        bytes.drain(..BOM.len());
+        true
+    } else {
+        false
+    }
+}
+
+/// Counts line endings of a file read line by line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineEndingCounter {
+    crlf: usize,
+    lf: usize,
+}
+
+impl LineEndingCounter {
+    /// Counts the line ending of `line`, and returns it without the line ending.
+    pub fn strip<'a>(&mut self, line: &'a [u8]) -> &'a [u8] {
+        let Some(line) = line.strip_suffix(b"\n") else {
+            return line;
+        };
+        self.lf += 1;
+        match line.strip_suffix(b"\r") {
+            Some(line) => {
+                self.crlf += 1;
+                line
+            }
+            None => line,
+        }
+    }
+
+    /// Returns the line ending used by most lines, or `None` if there were no line breaks.
+    pub fn detected(&self) -> Option<LineEnding> {
+        LineEnding::from_counts(self.crlf, self.lf)
+    }
+}
+
+/// Encodes the serialised list `text`, which uses `\n` line endings, for writing to a file.
+pub fn encode(text: &str, bom: bool, line_ending: LineEnding) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len() + BOM.len());
+    if bom {
+        bytes.extend_from_slice(BOM.as_bytes());
+    }
+    match line_ending {
+        LineEnding::Lf => bytes.extend_from_slice(text.as_bytes()),
+        LineEnding::CrLf => bytes.extend_from_slice(text.replace('\n', "\r\n").as_bytes()),
+    }
+    bytes
+}
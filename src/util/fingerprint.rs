@@ -0,0 +1,107 @@
+use std::{io, path::Path, time::SystemTime};
+
+/// Identifies one version of a file by its size, modification time and a hash of its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint {
+    size: u64,
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Fingerprint {
+    /// Creates the fingerprint of `contents`, last modified at `modified`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::Fingerprint;
+    ///
+    /// let a = Fingerprint::new(b"Task 1\n", None);
+    /// let b = Fingerprint::new(b"Task 1\n", Some(std::time::SystemTime::now()));
+    /// assert_eq!(a.size(), 7);
+    /// assert!(a.same_contents(&b));
+    /// assert!(!a.same_contents(&Fingerprint::new(b"Task 2\n", None)));
+    /// ```
+    pub fn new(contents: &[u8], modified: Option<SystemTime>) -> Fingerprint {
+        Fingerprint {
+            size: contents.len() as u64,
+            modified,
+            hash: fnv1a(OFFSET_BASIS, contents),
+        }
+    }
+
+    /// Returns the fingerprint of the contents followed by `appended`, last modified at
+    /// `modified`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::Fingerprint;
+    ///
+    /// let a = Fingerprint::new(b"Task 1\n", None).extended(b"Task 2\n", None);
+    /// assert!(a.same_contents(&Fingerprint::new(b"Task 1\nTask 2\n", None)));
+    /// ```
+    pub fn extended(&self, appended: &[u8], modified: Option<SystemTime>) -> Fingerprint {
+        Fingerprint {
+            size: self.size + appended.len() as u64,
+            modified,
+            hash: fnv1a(self.hash, appended),
+        }
+    }
+
+    /// Reads the file at `path` and creates its fingerprint.
+    ///
+    /// Will error if the file cannot be read.
+    pub fn of_file<P: AsRef<Path>>(path: P) -> std::io::Result<Fingerprint> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)?;
+        Ok(Fingerprint::new(&contents, modified(path)))
+    }
+
+    /// Returns the size of the contents in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the modification time, if known.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Returns the 64-bit FNV-1a hash of the contents.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns `true` if both fingerprints describe the same contents.
+    ///
+    /// The modification time is ignored, so a file that was only touched is unchanged.
+    pub fn same_contents(&self, other: &Fingerprint) -> bool {
+        self.size == other.size && self.hash == other.hash
+    }
+}
+
+/// Returns the size and modification time of the file at `path` from its metadata, or `None`
+/// if it does not exist.
+pub fn file_version<P: AsRef<Path>>(path: P) -> io::Result<Option<(u64, Option<SystemTime>)>> {
+    match std::fs::metadata(path) {
+        Ok(meta) => Ok(Some((meta.len(), meta.modified().ok()))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the modification time of the file at `path`, if it can be read.
+pub fn modified<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+// Continues the hash of some bytes, `hash`, with `bytes`
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
@@ -0,0 +1,186 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{AnansiError, error::AnansiResult};
+
+static LOCK_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Options for the advisory lock taken around loading and saving a list.
+///
+/// The lock is a file next to the list, named like the list with `.lock` appended, holding the
+/// id of the locking process and the time it was locked.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use anansi::LockOptions;
+///
+/// let options = LockOptions::new()
+///     .with_timeout(Duration::from_secs(2))
+///     .with_stale_after(Duration::from_secs(60));
+/// assert_eq!(options.timeout(), Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockOptions {
+    timeout: Duration,
+    stale_after: Duration,
+    retry_interval: Duration,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        LockOptions {
+            timeout: Duration::from_secs(10),
+            stale_after: Duration::from_secs(300),
+            retry_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+impl LockOptions {
+    /// Creates the default options: wait up to 10 seconds for the lock, and consider locks older
+    /// than 5 minutes stale.
+    pub fn new() -> LockOptions {
+        LockOptions::default()
+    }
+
+    /// Sets how long to wait for a lock held by someone else.
+    pub fn with_timeout(mut self, timeout: Duration) -> LockOptions {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the age after which a lock is considered abandoned and removed.
+    ///
+    /// On Linux, locks of processes that are no longer running are removed regardless of age.
+    pub fn with_stale_after(mut self, stale_after: Duration) -> LockOptions {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Sets how long to sleep between attempts to take the lock.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> LockOptions {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Returns how long to wait for a lock held by someone else.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the age after which a lock is considered abandoned.
+    pub fn stale_after(&self) -> Duration {
+        self.stale_after
+    }
+
+    /// Returns how long to sleep between attempts to take the lock.
+    pub fn retry_interval(&self) -> Duration {
+        self.retry_interval
+    }
+}
+
+/// A held lock on a file; released when dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+    token: String,
+}
+
+impl FileLock {
+    /// Locks `target`, waiting for other holders according to `options`.
+    ///
+    /// Will error with `AnansiError::Locked` if the lock is not released before the timeout.
+    pub fn acquire(target: &Path, options: &LockOptions) -> AnansiResult<FileLock> {
+        let path = lock_path(target);
+        let started = Instant::now();
+        loop {
+            let token = format!(
+                "{}\n{}\n{}\n",
+                std::process::id(),
+                millis_since_epoch(SystemTime::now()),
+                LOCK_COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    if let Err(err) = file.write_all(token.as_bytes()) {
+                        let _ = std::fs::remove_file(&path);
+                        return Err(err.into());
+                    }
+                    return Ok(FileLock { path, token });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+            if remove_if_stale(&path, options) {
+                continue;
+            }
+            if started.elapsed() >= options.timeout {
+                return Err(AnansiError::Locked(path));
+            }
+            std::thread::sleep(options.retry_interval);
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // Only remove the lock if it was not broken and taken by someone else in the meantime
+        if std::fs::read_to_string(&self.path).is_ok_and(|held| held == self.token) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn lock_path(target: &Path) -> PathBuf {
+    let mut path = target.to_path_buf().into_os_string();
+    path.push(".lock");
+    PathBuf::from(path)
+}
+
+fn millis_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Removes the lock at `path` if it was abandoned. Returns `true` if it was removed.
+fn remove_if_stale(path: &Path, options: &LockOptions) -> bool {
+    let Ok(held) = std::fs::read_to_string(path) else {
+        // Released in the meantime
+        return !path.exists();
+    };
+    let mut fields = held.lines();
+    let pid = fields.next().and_then(|pid| pid.parse::<u32>().ok());
+    let locked_at = fields.next().and_then(|millis| millis.parse::<u128>().ok());
+    let age = match locked_at {
+        Some(locked_at) => millis_since_epoch(SystemTime::now()).saturating_sub(locked_at),
+        // Still being written, or garbage; fall back to the age of the file
+        None => std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .map_or(0, |age| age.as_millis()),
+    };
+    let stale = age >= options.stale_after.as_millis() || pid.is_some_and(|pid| !is_running(pid));
+    // Re-check the contents to narrow the window in which a fresh lock could be removed
+    if stale && std::fs::read_to_string(path).is_ok_and(|current| current == held) {
+        return std::fs::remove_file(path).is_ok();
+    }
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    !Path::new("/proc").is_dir() || Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
@@ -1,9 +1,22 @@
+mod atomic;
 mod date;
+mod encoding;
+mod fingerprint;
+mod lock;
 mod sort_by;
+mod sort_spec;
 mod test;
 
+pub use atomic::{parent_dir, write_atomic, write_new};
 pub use date::*;
+pub use encoding::{
+    BOM, DecodeIssue, Decoded, LineEnding, LineEndingCounter, decode, decode_line, encode,
+    strip_bom,
+};
+pub use fingerprint::{Fingerprint, file_version, modified};
+pub use lock::{FileLock, LockOptions};
 pub use sort_by::SortBy;
+pub use sort_spec::{NullsPlacement, SortDirection, SortKey, SortSpec, SortTerm, sorted_indices};
 
 pub fn deserialise_date<S: AsRef<str>>(input: S) -> Date {
     let split_check = input.as_ref().split('-').collect::<Vec<&str>>();
@@ -28,3 +41,20 @@ pub fn deserialise_date<S: AsRef<str>>(input: S) -> Date {
         Date::default()
     }
 }
+
+/// Replaces every whitespace separated token in `text` that is exactly `old` with `new`,
+/// keeping the whitespace between tokens as it is.
+pub fn replace_token(text: &str, old: &str, new: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+        replaced.push_str(if token == old { new } else { token });
+        rest = &rest[end..];
+    }
+    replaced.push_str(rest);
+    replaced
+}
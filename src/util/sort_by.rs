@@ -1,5 +1,24 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
+use crate::AnansiError;
+
+/// The basic sort keys.
+///
+/// Use `SortBy::id` to persist a `SortBy`; the id is stable and can be parsed back with
+/// `str::parse` or `SortBy::try_from`. `SortBy::label` and the `Display` implementation return a
+/// human readable label, which is also accepted when parsing.
+///
+/// # Example
+/// ```
+/// use anansi::SortBy;
+///
+/// let sort_by = SortBy::InceptionDate;
+/// assert_eq!(sort_by.id(), "inception_date");
+/// assert_eq!(sort_by.label(), "Inception date");
+/// assert_eq!("inception_date".parse::<SortBy>().unwrap(), sort_by);
+/// assert_eq!(SortBy::try_from("Inception date").unwrap(), sort_by);
+/// assert!("Due date".parse::<SortBy>().is_err());
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SortBy {
     Priority,
@@ -7,29 +26,67 @@ pub enum SortBy {
     CompletionDate,
 }
 
-impl Into<String> for SortBy {
-    fn into(self) -> String {
+impl SortBy {
+    /// Returns the stable identifier of the `SortBy`, meant for persisting.
+    pub fn id(&self) -> &'static str {
         match self {
-            SortBy::Priority => "Priority".to_string(),
-            SortBy::InceptionDate => "Inception date".to_string(),
-            SortBy::CompletionDate => "Completion date".to_string(),
+            SortBy::Priority => "priority",
+            SortBy::InceptionDate => "inception_date",
+            SortBy::CompletionDate => "completion_date",
         }
     }
+
+    /// Returns the human readable label of the `SortBy`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortBy::Priority => "Priority",
+            SortBy::InceptionDate => "Inception date",
+            SortBy::CompletionDate => "Completion date",
+        }
+    }
+}
+
+impl From<SortBy> for String {
+    fn from(sort_by: SortBy) -> Self {
+        sort_by.label().to_string()
+    }
 }
 
-impl From<String> for SortBy {
-    fn from(input: String) -> Self {
-        match input.as_str() {
-            "Priority" => SortBy::Priority,
-            "Inception date" => SortBy::InceptionDate,
-            "Completion date" => SortBy::CompletionDate,
-            _ => panic!("Invalid SortBy"),
+impl FromStr for SortBy {
+    type Err = AnansiError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        for sort_by in [
+            SortBy::Priority,
+            SortBy::InceptionDate,
+            SortBy::CompletionDate,
+        ] {
+            if input == sort_by.id() || input == sort_by.label() {
+                return Ok(sort_by);
+            }
         }
+        Err(AnansiError::InvalidSort(format!(
+            "Invalid SortBy: {}",
+            input
+        )))
+    }
+}
+
+impl TryFrom<&str> for SortBy {
+    type Error = AnansiError;
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for SortBy {
+    type Error = AnansiError;
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
     }
 }
 
 impl Display for SortBy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", Into::<String>::into(*self))
+        write!(f, "{}", self.label())
     }
 }
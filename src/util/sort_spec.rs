@@ -0,0 +1,601 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use crate::{AnansiError, Date, SortBy, Task};
+
+/// A single property tasks can be sorted by.
+///
+/// Any key can be missing on a task (no priority, no project, ...); where those tasks end up is
+/// decided by the `NullsPlacement` of the `SortTerm` the key is used in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortKey {
+    /// Priority, `A` first when ascending.
+    Priority,
+    /// Inception date, oldest first when ascending.
+    InceptionDate,
+    /// Completion date, oldest first when ascending.
+    CompletionDate,
+    /// The `due:` special tag, compared as a date. Values that are not a valid date count as missing.
+    DueDate,
+    /// The task text, compared case insensitively.
+    Text,
+    /// The task id, which is the file order of a freshly loaded list.
+    Id,
+    /// Done state, open tasks first when ascending.
+    Done,
+    /// The first project tag, compared case insensitively.
+    Project,
+    /// The first context tag, compared case insensitively.
+    Context,
+    /// The value of the special tag with the given key.
+    ///
+    /// Each value is read as a number if it is one, else as a date if it is one, else as case
+    /// insensitive text. Values of the same kind are compared as such; of different kinds,
+    /// numbers come before dates and dates before text.
+    Special(String),
+}
+
+/// Direction of a `SortTerm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Where tasks without a value for the sort key are placed, regardless of the `SortDirection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullsPlacement {
+    First,
+    #[default]
+    Last,
+}
+
+/// A `SortKey` together with its direction and the placement of tasks missing the key.
+///
+/// Defaults to ascending with missing values last.
+///
+/// # Example
+/// ```
+/// use anansi::{SortKey, SortTerm, SortDirection, NullsPlacement};
+///
+/// let term = SortTerm::new(SortKey::DueDate).descending().nulls_first();
+/// assert_eq!(term.key(), &SortKey::DueDate);
+/// assert_eq!(term.direction(), SortDirection::Descending);
+/// assert_eq!(term.nulls(), NullsPlacement::First);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortTerm {
+    key: SortKey,
+    direction: SortDirection,
+    nulls: NullsPlacement,
+}
+
+impl SortTerm {
+    /// Creates a new ascending term with missing values last.
+    pub fn new(key: SortKey) -> SortTerm {
+        SortTerm {
+            key,
+            direction: SortDirection::default(),
+            nulls: NullsPlacement::default(),
+        }
+    }
+    /// Sorts in ascending order and returns the updated term.
+    pub fn ascending(mut self) -> SortTerm {
+        self.direction = SortDirection::Ascending;
+        self
+    }
+    /// Sorts in descending order and returns the updated term.
+    pub fn descending(mut self) -> SortTerm {
+        self.direction = SortDirection::Descending;
+        self
+    }
+    /// Places tasks missing the key first and returns the updated term.
+    pub fn nulls_first(mut self) -> SortTerm {
+        self.nulls = NullsPlacement::First;
+        self
+    }
+    /// Places tasks missing the key last and returns the updated term.
+    pub fn nulls_last(mut self) -> SortTerm {
+        self.nulls = NullsPlacement::Last;
+        self
+    }
+    /// Returns the key of the term.
+    pub fn key(&self) -> &SortKey {
+        &self.key
+    }
+    /// Returns the direction of the term.
+    pub fn direction(&self) -> SortDirection {
+        self.direction
+    }
+    /// Returns the placement of tasks missing the key.
+    pub fn nulls(&self) -> NullsPlacement {
+        self.nulls
+    }
+}
+
+/// An ordered list of `SortTerm`s.
+///
+/// Tasks are compared by the first term; ties are broken by the next term and so on.
+/// Sorting is stable, so tasks equal in all terms keep their relative order.
+///
+/// Anything taking a `SortSpec` also accepts a `SortBy`, `SortKey` or `SortTerm`.
+///
+/// Use `SortSpec::id` to persist a spec and `str::parse` to restore it. The `Display`
+/// implementation returns a human readable label instead.
+///
+/// # Example
+/// ```
+/// use anansi::{List, SortKey, SortSpec, SortTerm};
+///
+/// let mut list = List::new("path/to/list.txt");
+/// list.add("(B) Task 1 due:2022-01-02");
+/// list.add("(A) Task 2");
+/// list.add("(B) Task 3 due:2022-01-01");
+///
+/// let spec = SortSpec::new()
+///     .then(SortKey::Priority)
+///     .then(SortTerm::new(SortKey::DueDate).nulls_first());
+/// let sorted = list.sorted(spec);
+/// assert_eq!(sorted[0].text(), "Task 2");
+/// assert_eq!(sorted[1].text(), "Task 3 due:2022-01-01");
+/// assert_eq!(sorted[2].text(), "Task 1 due:2022-01-02");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SortSpec {
+    terms: Vec<SortTerm>,
+}
+
+impl SortSpec {
+    /// Creates a new, empty spec. Sorting by an empty spec keeps the original order.
+    pub fn new() -> SortSpec {
+        SortSpec { terms: Vec::new() }
+    }
+    /// Appends a term and returns the updated spec.
+    pub fn then<T: Into<SortTerm>>(mut self, term: T) -> SortSpec {
+        self.terms.push(term.into());
+        self
+    }
+    /// Returns all terms in order.
+    pub fn terms(&self) -> &[SortTerm] {
+        &self.terms
+    }
+}
+
+impl From<SortBy> for SortKey {
+    fn from(sort_by: SortBy) -> Self {
+        match sort_by {
+            SortBy::Priority => SortKey::Priority,
+            SortBy::InceptionDate => SortKey::InceptionDate,
+            SortBy::CompletionDate => SortKey::CompletionDate,
+        }
+    }
+}
+
+impl From<SortKey> for SortTerm {
+    fn from(key: SortKey) -> Self {
+        SortTerm::new(key)
+    }
+}
+
+impl From<SortBy> for SortTerm {
+    fn from(sort_by: SortBy) -> Self {
+        SortTerm::new(sort_by.into())
+    }
+}
+
+impl From<SortTerm> for SortSpec {
+    fn from(term: SortTerm) -> Self {
+        SortSpec { terms: vec![term] }
+    }
+}
+
+impl From<SortKey> for SortSpec {
+    fn from(key: SortKey) -> Self {
+        SortSpec::from(SortTerm::new(key))
+    }
+}
+
+impl From<SortBy> for SortSpec {
+    fn from(sort_by: SortBy) -> Self {
+        SortSpec::from(SortTerm::from(sort_by))
+    }
+}
+
+impl From<Vec<SortTerm>> for SortSpec {
+    fn from(terms: Vec<SortTerm>) -> Self {
+        SortSpec { terms }
+    }
+}
+
+// ---------------------------------------------------------------
+//                        Encoding
+// ---------------------------------------------------------------
+// A spec is encoded as its terms joined by `,`; a term as `key:direction:nulls`,
+// e.g. `priority:asc:last,special.due:desc:first`.
+// Special tag keys may contain `,`, so `%`, `,` and `:` are percent encoded.
+
+const SPECIAL_PREFIX: &str = "special.";
+
+fn escape_key(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '%' => out.push_str("%25"),
+            ',' => out.push_str("%2C"),
+            ':' => out.push_str("%3A"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_key(key: &str) -> Option<String> {
+    let mut out = String::with_capacity(key.len());
+    let mut rest = key;
+    while let Some(idx) = rest.find('%') {
+        out.push_str(&rest[..idx]);
+        let code = rest.get(idx + 1..idx + 3)?;
+        out.push(u8::from_str_radix(code, 16).ok()? as char);
+        rest = &rest[idx + 3..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+fn invalid(kind: &str, input: &str) -> AnansiError {
+    AnansiError::InvalidSort(format!("Invalid {}: {}", kind, input))
+}
+
+impl SortKey {
+    /// Returns the stable identifier of the key, meant for persisting.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::SortKey;
+    ///
+    /// assert_eq!(SortKey::DueDate.id(), "due_date");
+    /// assert_eq!(SortKey::Special("time".to_string()).id(), "special.time");
+    /// ```
+    pub fn id(&self) -> String {
+        match self {
+            SortKey::Priority => SortBy::Priority.id().to_string(),
+            SortKey::InceptionDate => SortBy::InceptionDate.id().to_string(),
+            SortKey::CompletionDate => SortBy::CompletionDate.id().to_string(),
+            SortKey::DueDate => "due_date".to_string(),
+            SortKey::Text => "text".to_string(),
+            SortKey::Id => "id".to_string(),
+            SortKey::Done => "done".to_string(),
+            SortKey::Project => "project".to_string(),
+            SortKey::Context => "context".to_string(),
+            SortKey::Special(key) => format!("{}{}", SPECIAL_PREFIX, escape_key(key)),
+        }
+    }
+
+    /// Returns the human readable label of the key.
+    pub fn label(&self) -> String {
+        match self {
+            SortKey::Priority => SortBy::Priority.label().to_string(),
+            SortKey::InceptionDate => SortBy::InceptionDate.label().to_string(),
+            SortKey::CompletionDate => SortBy::CompletionDate.label().to_string(),
+            SortKey::DueDate => "Due date".to_string(),
+            SortKey::Text => "Text".to_string(),
+            SortKey::Id => "ID".to_string(),
+            SortKey::Done => "Done".to_string(),
+            SortKey::Project => "Project".to_string(),
+            SortKey::Context => "Context".to_string(),
+            SortKey::Special(key) => format!("Special tag {}", key),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = AnansiError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Ok(sort_by) = input.parse::<SortBy>() {
+            return Ok(sort_by.into());
+        }
+        if let Some(key) = input.strip_prefix(SPECIAL_PREFIX) {
+            return match unescape_key(key) {
+                Some(key) if !key.is_empty() => Ok(SortKey::Special(key)),
+                _ => Err(invalid("SortKey", input)),
+            };
+        }
+        for key in [
+            SortKey::DueDate,
+            SortKey::Text,
+            SortKey::Id,
+            SortKey::Done,
+            SortKey::Project,
+            SortKey::Context,
+        ] {
+            if input == key.id() || input == key.label() {
+                return Ok(key);
+            }
+        }
+        Err(invalid("SortKey", input))
+    }
+}
+
+impl SortTerm {
+    /// Returns the stable identifier of the term, meant for persisting.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{SortKey, SortTerm};
+    ///
+    /// let term = SortTerm::new(SortKey::Priority).descending();
+    /// assert_eq!(term.id(), "priority:desc:last");
+    /// assert_eq!(term.id().parse::<SortTerm>().unwrap(), term);
+    /// assert_eq!("priority".parse::<SortTerm>().unwrap(), SortTerm::new(SortKey::Priority));
+    /// ```
+    pub fn id(&self) -> String {
+        let direction = match self.direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        };
+        let nulls = match self.nulls {
+            NullsPlacement::First => "first",
+            NullsPlacement::Last => "last",
+        };
+        format!("{}:{}:{}", self.key.id(), direction, nulls)
+    }
+
+    /// Returns the human readable label of the term.
+    pub fn label(&self) -> String {
+        let mut notes = Vec::new();
+        if self.direction == SortDirection::Descending {
+            notes.push("descending");
+        }
+        if self.nulls == NullsPlacement::First {
+            notes.push("missing first");
+        }
+        if notes.is_empty() {
+            self.key.label()
+        } else {
+            format!("{} ({})", self.key.label(), notes.join(", "))
+        }
+    }
+}
+
+impl FromStr for SortTerm {
+    type Err = AnansiError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parts = input.split(':');
+        let key = parts.next().unwrap_or_default().parse::<SortKey>()?;
+        let mut term = SortTerm::new(key);
+        match parts.next() {
+            None | Some("asc") => {}
+            Some("desc") => term = term.descending(),
+            Some(_) => return Err(invalid("SortTerm", input)),
+        }
+        match parts.next() {
+            None | Some("last") => {}
+            Some("first") => term = term.nulls_first(),
+            Some(_) => return Err(invalid("SortTerm", input)),
+        }
+        if parts.next().is_some() {
+            return Err(invalid("SortTerm", input));
+        }
+        Ok(term)
+    }
+}
+
+impl SortSpec {
+    /// Returns the stable identifier of the spec, meant for persisting.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{SortKey, SortSpec, SortTerm};
+    ///
+    /// let spec = SortSpec::new()
+    ///     .then(SortKey::Priority)
+    ///     .then(SortTerm::new(SortKey::Special("due,soft".to_string())).nulls_first());
+    /// let id = spec.id();
+    /// assert_eq!(id, "priority:asc:last,special.due%2Csoft:asc:first");
+    /// assert_eq!(id.parse::<SortSpec>().unwrap(), spec);
+    ///
+    /// // Labels of `SortBy` persisted by older versions are understood as well
+    /// assert_eq!(SortSpec::try_from("Inception date").unwrap(), SortKey::InceptionDate.into());
+    /// assert!(SortSpec::try_from("priority:sideways").is_err());
+    /// ```
+    pub fn id(&self) -> String {
+        self.terms
+            .iter()
+            .map(SortTerm::id)
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    /// Returns the human readable label of the spec.
+    pub fn label(&self) -> String {
+        self.terms
+            .iter()
+            .map(SortTerm::label)
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl FromStr for SortSpec {
+    type Err = AnansiError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut spec = SortSpec::new();
+        if input.trim().is_empty() {
+            return Ok(spec);
+        }
+        for term in input.split(',') {
+            spec = spec.then(term.trim().parse::<SortTerm>()?);
+        }
+        Ok(spec)
+    }
+}
+
+impl TryFrom<&str> for SortSpec {
+    type Error = AnansiError;
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for SortSpec {
+    type Error = AnansiError;
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl Display for SortTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl Display for SortSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+// ---------------------------------------------------------------
+//                        Comparison
+// ---------------------------------------------------------------
+
+// Variants are declared in the order mixed values of a special tag are sorted in.
+#[derive(Debug, Clone)]
+enum SortValue {
+    Bool(bool),
+    Id(usize),
+    Char(char),
+    Number(f64),
+    Date(Date),
+    Text(String),
+}
+
+impl SortValue {
+    fn rank(&self) -> u8 {
+        match self {
+            SortValue::Bool(_) => 0,
+            SortValue::Id(_) => 1,
+            SortValue::Char(_) => 2,
+            SortValue::Number(_) => 3,
+            SortValue::Date(_) => 4,
+            SortValue::Text(_) => 5,
+        }
+    }
+
+    fn compare(&self, other: &SortValue) -> Ordering {
+        match (self, other) {
+            (SortValue::Bool(a), SortValue::Bool(b)) => a.cmp(b),
+            (SortValue::Id(a), SortValue::Id(b)) => a.cmp(b),
+            (SortValue::Char(a), SortValue::Char(b)) => a.cmp(b),
+            (SortValue::Number(a), SortValue::Number(b)) => a.total_cmp(b),
+            (SortValue::Date(a), SortValue::Date(b)) => a.cmp(b),
+            (SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+fn set_date(date: String) -> Option<SortValue> {
+    let date = Date::from(date);
+    if date.is_set() {
+        Some(SortValue::Date(date))
+    } else {
+        None
+    }
+}
+
+fn special_value<'a>(task: &'a Task, key: &str) -> Option<&'a str> {
+    if let Some(value) = task.special(key) {
+        return Some(value);
+    }
+    task.specials()
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+fn typed_value(value: &str) -> SortValue {
+    if let Ok(number) = value.parse::<f64>()
+        && number.is_finite()
+    {
+        return SortValue::Number(number);
+    }
+    let date = Date::from(value);
+    if date.is_set() {
+        SortValue::Date(date)
+    } else {
+        SortValue::Text(value.to_lowercase())
+    }
+}
+
+fn sort_value(task: &Task, key: &SortKey) -> Option<SortValue> {
+    match key {
+        SortKey::Priority => task.prio().map(SortValue::Char),
+        SortKey::InceptionDate => set_date(task.inception_date()),
+        SortKey::CompletionDate => set_date(task.completion_date()),
+        SortKey::DueDate => special_value(task, "due").and_then(|due| set_date(due.to_string())),
+        SortKey::Text => Some(SortValue::Text(task.text().to_lowercase())),
+        SortKey::Id => Some(SortValue::Id(task.id())),
+        SortKey::Done => Some(SortValue::Bool(task.is_done())),
+        SortKey::Project => task
+            .iter_projects()
+            .next()
+            .map(|tag| SortValue::Text(tag.to_lowercase())),
+        SortKey::Context => task
+            .iter_contexts()
+            .next()
+            .map(|tag| SortValue::Text(tag.to_lowercase())),
+        SortKey::Special(key) => special_value(task, key).map(typed_value),
+    }
+}
+
+fn compare_values(a: &[Option<SortValue>], b: &[Option<SortValue>], spec: &SortSpec) -> Ordering {
+    for ((a, b), term) in a.iter().zip(b).zip(spec.terms()) {
+        let ordering = match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => match term.nulls {
+                NullsPlacement::First => Ordering::Less,
+                NullsPlacement::Last => Ordering::Greater,
+            },
+            (Some(_), None) => match term.nulls {
+                NullsPlacement::First => Ordering::Greater,
+                NullsPlacement::Last => Ordering::Less,
+            },
+            (Some(a), Some(b)) => match term.direction {
+                SortDirection::Ascending => a.compare(b),
+                SortDirection::Descending => b.compare(a),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Returns the indices of `tasks` in sorted order.
+///
+/// Every key is extracted once per task, not once per comparison.
+pub fn sorted_indices(tasks: &[&Task], spec: &SortSpec) -> Vec<usize> {
+    let mut decorated: Vec<(Vec<Option<SortValue>>, usize)> = tasks
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| {
+            let values = spec
+                .terms()
+                .iter()
+                .map(|term| sort_value(task, &term.key))
+                .collect();
+            (values, idx)
+        })
+        .collect();
+    decorated.sort_by(|(a, _), (b, _)| compare_values(a, b, spec));
+    decorated.into_iter().map(|(_, idx)| idx).collect()
+}
@@ -78,3 +78,99 @@ mod dates {
         }
     }
 }
+
+#[cfg(test)]
+mod sorting {
+    use crate::{AnansiError, SortBy, SortKey, SortSpec, SortTerm};
+
+    #[test]
+    fn sort_by_round_trip() {
+        for sort_by in [
+            SortBy::Priority,
+            SortBy::InceptionDate,
+            SortBy::CompletionDate,
+        ] {
+            assert_eq!(sort_by.id().parse::<SortBy>().unwrap(), sort_by);
+            assert_eq!(SortBy::try_from(sort_by.to_string()).unwrap(), sort_by);
+        }
+        assert!(matches!(
+            SortBy::try_from("Invalid"),
+            Err(AnansiError::InvalidSort(_))
+        ));
+    }
+
+    #[test]
+    fn sort_spec_round_trip() {
+        let keys = vec![
+            SortKey::Priority,
+            SortKey::InceptionDate,
+            SortKey::CompletionDate,
+            SortKey::DueDate,
+            SortKey::Text,
+            SortKey::Id,
+            SortKey::Done,
+            SortKey::Project,
+            SortKey::Context,
+            SortKey::Special("100%,done:".to_string()),
+        ];
+        let mut spec = SortSpec::new();
+        for (n, key) in keys.into_iter().enumerate() {
+            let mut term = SortTerm::new(key);
+            if n % 2 == 0 {
+                term = term.descending();
+            }
+            if n % 3 == 0 {
+                term = term.nulls_first();
+            }
+            assert_eq!(term.id().parse::<SortTerm>().unwrap(), term);
+            spec = spec.then(term);
+        }
+        assert_eq!(spec.id().parse::<SortSpec>().unwrap(), spec);
+        assert_eq!("".parse::<SortSpec>().unwrap(), SortSpec::new());
+        assert!("special.".parse::<SortSpec>().is_err());
+        assert!("special.%2".parse::<SortSpec>().is_err());
+        assert!("priority:asc:last:extra".parse::<SortSpec>().is_err());
+    }
+}
+
+#[cfg(test)]
+mod tokens {
+    use crate::util::replace_token;
+
+    #[test]
+    fn only_whole_tokens_are_replaced() {
+        assert_eq!(
+            replace_token("Task  ref:uid:3 uid:3\tuid:31", "uid:3", "uid:7"),
+            "Task  ref:uid:3 uid:7\tuid:31"
+        );
+        assert_eq!(replace_token(" uid:3 ", "uid:3", "uid:7"), " uid:7 ");
+        assert_eq!(replace_token("", "uid:3", "uid:7"), "");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod atomic {
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    use crate::util::write_atomic;
+
+    #[test]
+    fn keeps_permissions_and_symlinks() {
+        let dir = std::path::PathBuf::from("test-data/atomic-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.txt");
+        let link = dir.join("todo.txt");
+        std::fs::write(&target, "Task 1\n").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+        symlink("target.txt", &link).unwrap();
+
+        write_atomic(&link, b"Task 2\n").unwrap();
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "Task 2\n");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
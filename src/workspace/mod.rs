@@ -0,0 +1,425 @@
+mod test;
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    AnansiError, GroupKey, GroupName, List, Task, TransferOptions,
+    error::AnansiResult,
+    list::group_names,
+    util::{SortSpec, sorted_indices},
+};
+
+/// Identifies a task in a `Workspace`: the name of its list and its id in that list.
+///
+/// Formatted as `list:id`, e.g. `work:3`, and parsed from the same format.
+///
+/// # Example
+/// ```
+/// use anansi::GlobalId;
+///
+/// let id: GlobalId = "work:3".parse().unwrap();
+/// assert_eq!(id.list(), "work");
+/// assert_eq!(id.id(), 3);
+/// assert_eq!(id.to_string(), "work:3");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlobalId {
+    list: String,
+    id: usize,
+}
+
+impl GlobalId {
+    /// Creates the id of task `id` in the list named `list`.
+    pub fn new<S: Into<String>>(list: S, id: usize) -> GlobalId {
+        GlobalId {
+            list: list.into(),
+            id,
+        }
+    }
+
+    /// Returns the name of the list.
+    pub fn list(&self) -> &str {
+        &self.list
+    }
+
+    /// Returns the id of the task in its list.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Display for GlobalId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.list, self.id)
+    }
+}
+
+impl FromStr for GlobalId {
+    type Err = AnansiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // List names may contain colons, ids cannot
+        s.rsplit_once(':')
+            .and_then(|(list, id)| Some(GlobalId::new(list, id.parse().ok()?)))
+            .ok_or_else(|| AnansiError::InvalidID(format!("Invalid global id: {}", s)))
+    }
+}
+
+/// Several named lists, e.g. `work.txt`, `home.txt` and `done.txt`, used together.
+///
+/// Tasks are identified across lists by a `GlobalId`. Queries, sorting and grouping work on
+/// the tasks of all lists; lists are ordered by name, tasks by id within their list.
+///
+/// # Example
+/// ```
+/// use anansi::{List, Workspace};
+///
+/// let mut workspace = Workspace::new();
+/// workspace.insert("work", List::from_reader("Task 1 +report\n".as_bytes()).unwrap());
+/// workspace.insert("home", List::from_reader("Task 2 +report\n".as_bytes()).unwrap());
+///
+/// let ids = workspace.by_project("report");
+/// assert_eq!(ids.len(), 2);
+/// assert_eq!(ids[0].to_string(), "home:1");
+/// assert_eq!(workspace.task(&ids[1]).unwrap().text(), "Task 1 +report");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    lists: BTreeMap<String, List>,
+    // Names of the lists changed by each move or copy, oldest first
+    transfers: Vec<Vec<String>>,
+}
+
+impl Workspace {
+    /// Creates an empty workspace.
+    pub fn new() -> Workspace {
+        Workspace::default()
+    }
+
+    /// Opens every `.txt` file in `dir` as a list, named after the file without the extension.
+    ///
+    /// Will error if the directory or one of the files cannot be read.
+    pub fn open_dir<P: AsRef<Path>>(dir: P) -> AnansiResult<Workspace> {
+        let mut workspace = Workspace::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "txt") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let name = name.to_string();
+            workspace.insert(name, List::load(path)?);
+        }
+        Ok(workspace)
+    }
+
+    /// Adds the list at `path` under `name`; a missing file is a new, empty list.
+    ///
+    /// Returns the list previously known by that name, if any.
+    pub fn open<S: Into<String>, P: Into<PathBuf>>(&mut self, name: S, path: P) -> Option<List> {
+        self.insert(name, List::new(path))
+    }
+
+    /// Adds `list` under `name`. Returns the list previously known by that name, if any.
+    pub fn insert<S: Into<String>>(&mut self, name: S, list: List) -> Option<List> {
+        self.lists.insert(name.into(), list)
+    }
+
+    /// Removes the list named `name` from the workspace and returns it.
+    pub fn remove(&mut self, name: &str) -> Option<List> {
+        self.lists.remove(name)
+    }
+
+    /// Returns the list named `name`.
+    pub fn get(&self, name: &str) -> Option<&List> {
+        self.lists.get(name)
+    }
+
+    /// Returns the list named `name` for changing it.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut List> {
+        self.lists.get_mut(name)
+    }
+
+    /// Returns the names of all lists, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.lists.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the task with the given id.
+    pub fn task(&self, id: &GlobalId) -> Option<&Task> {
+        self.lists.get(&id.list)?.get(id.id)
+    }
+
+    /// Returns all tasks of all lists with their ids.
+    pub fn tasks(&self) -> Vec<(GlobalId, &Task)> {
+        self.filter(|_| true)
+    }
+
+    /// Returns the tasks of all lists for which `predicate` returns `true`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, Workspace};
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", List::from_reader("x Task 1\nTask 2\n".as_bytes()).unwrap());
+    /// workspace.insert("home", List::from_reader("x Task 3\n".as_bytes()).unwrap());
+    /// assert_eq!(workspace.filter(|task| task.is_done()).len(), 2);
+    /// ```
+    pub fn filter<F: Fn(&Task) -> bool>(&self, predicate: F) -> Vec<(GlobalId, &Task)> {
+        self.lists
+            .iter()
+            .flat_map(|(name, list)| {
+                list.entries()
+                    .filter(|(_, task)| predicate(task))
+                    .map(|(id, task)| (GlobalId::new(name.clone(), id), task))
+            })
+            .collect()
+    }
+
+    /// Returns the ids of all tasks with the given project tag, matched case insensitively.
+    pub fn by_project(&self, project: &str) -> Vec<GlobalId> {
+        self.collect_ids(|list| list.ids_by_project(project))
+    }
+
+    /// Returns the ids of all tasks with the given context tag, matched case insensitively.
+    pub fn by_context(&self, context: &str) -> Vec<GlobalId> {
+        self.collect_ids(|list| list.ids_by_context(context))
+    }
+
+    /// Sort the tasks of all lists.
+    ///
+    /// Sorting is stable; tasks equal in every key stay in list and id order.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{List, SortKey, Workspace};
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", List::from_reader("(B) Task 1\n".as_bytes()).unwrap());
+    /// workspace.insert("home", List::from_reader("(C) Task 2\n(A) Task 3\n".as_bytes()).unwrap());
+    ///
+    /// let sorted = workspace.sorted(SortKey::Priority);
+    /// let texts: Vec<&str> = sorted.iter().map(|(_, task)| task.text()).collect();
+    /// assert_eq!(texts, vec!["Task 3", "Task 1", "Task 2"]);
+    /// ```
+    pub fn sorted<S: Into<SortSpec>>(&self, sort_by: S) -> Vec<(GlobalId, &Task)> {
+        let mut tasks: Vec<Option<(GlobalId, &Task)>> =
+            self.tasks().into_iter().map(Some).collect();
+        let refs: Vec<&Task> = tasks.iter().flatten().map(|(_, task)| *task).collect();
+        sorted_indices(&refs, &sort_by.into())
+            .into_iter()
+            .filter_map(|idx| tasks[idx].take())
+            .collect()
+    }
+
+    /// Group the tasks of all lists by the given key.
+    ///
+    /// Like `List::group_by`, tasks lacking the key are in the `GroupName::None` group.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{GroupKey, GroupName, List, Workspace};
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", List::from_reader("Task 1 +garden\n".as_bytes()).unwrap());
+    /// workspace.insert("home", List::from_reader("Task 2 +garden\nTask 3\n".as_bytes()).unwrap());
+    ///
+    /// let groups = workspace.group_by(GroupKey::Project);
+    /// assert_eq!(groups[&GroupName::Value("garden".to_string())].len(), 2);
+    /// assert_eq!(groups[&GroupName::None].len(), 1);
+    /// ```
+    pub fn group_by(&self, key: GroupKey) -> BTreeMap<GroupName, Vec<GlobalId>> {
+        let mut groups: BTreeMap<GroupName, Vec<GlobalId>> = BTreeMap::new();
+        for (id, task) in self.tasks() {
+            for name in group_names(task, &key) {
+                groups.entry(name).or_default().push(id.clone());
+            }
+        }
+        groups
+    }
+
+    /// Move a task to the end of the list named `to`.
+    ///
+    /// Same as `move_task_with` with the default `TransferOptions`.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{GlobalId, List, Workspace};
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", List::from_reader("Task 1\n".as_bytes()).unwrap());
+    /// workspace.insert("home", List::from_reader("Task 2\n".as_bytes()).unwrap());
+    ///
+    /// let moved = workspace.move_task(&GlobalId::new("work", 1), "home").unwrap();
+    /// assert_eq!(moved, GlobalId::new("home", 2));
+    /// assert_eq!(workspace.get("work").unwrap().task_amount(), 0);
+    /// ```
+    pub fn move_task(&mut self, id: &GlobalId, to: &str) -> AnansiResult<GlobalId> {
+        self.move_task_with(id, to, &TransferOptions::default())
+    }
+
+    /// Move a task to the end of the list named `to`, as `List::move_to_with` does.
+    ///
+    /// Returns the id of the task in its new list. Moving a task to its own list does
+    /// nothing. Will error if the task or either list does not exist.
+    ///
+    /// The move is undone in both lists at once by `undo_transfer`.
+    pub fn move_task_with(
+        &mut self,
+        id: &GlobalId,
+        to: &str,
+        options: &TransferOptions,
+    ) -> AnansiResult<GlobalId> {
+        if id.list == to {
+            return match self.task(id) {
+                Some(_) => Ok(id.clone()),
+                None => Err(unknown_task(id)),
+            };
+        }
+        let ids = self.transfer(&id.list, to, |from, to| {
+            from.move_to_with(to, [id.id], options)
+        })?;
+        self.transfers.push(vec![id.list.clone(), to.to_string()]);
+        Ok(GlobalId::new(to, ids[0]))
+    }
+
+    /// Copy a task to the end of the list named `to`, as `List::copy_to_with` does.
+    ///
+    /// Returns the id of the copy. Will error if the task or either list does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{GlobalId, List, TransferOptions, Workspace};
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", List::from_reader("Task 1\n".as_bytes()).unwrap());
+    /// workspace.insert("home", List::from_reader("".as_bytes()).unwrap());
+    ///
+    /// let options = TransferOptions::new().with_provenance("work");
+    /// let copy = workspace.copy_task(&GlobalId::new("work", 1), "home", &options).unwrap();
+    /// assert_eq!(workspace.task(&copy).unwrap().text(), "Task 1 from:work");
+    /// assert_eq!(workspace.get("work").unwrap().task_amount(), 1);
+    /// ```
+    pub fn copy_task(
+        &mut self,
+        id: &GlobalId,
+        to: &str,
+        options: &TransferOptions,
+    ) -> AnansiResult<GlobalId> {
+        let task = self.task(id).cloned().ok_or_else(|| unknown_task(id))?;
+        if id.list == to {
+            let list = self.list_mut(to)?;
+            let copy = list.push_task(options.apply(task));
+            self.transfers.push(vec![to.to_string()]);
+            return Ok(GlobalId::new(to, copy));
+        }
+        let ids = self.transfer(&id.list, to, |from, to| {
+            from.copy_to_with(to, [id.id], options)
+        })?;
+        self.transfers.push(vec![to.to_string()]);
+        Ok(GlobalId::new(to, ids[0]))
+    }
+
+    /// Undo the last `move_task` or `copy_task` in every list it changed, as one step.
+    ///
+    /// Each of those lists undoes its last step, so they need a history, see
+    /// `List::set_history_depth`, and should not have been changed otherwise since.
+    /// Returns `false` without changing anything if there is no transfer to undo, or one of
+    /// its lists is gone or has nothing to undo.
+    ///
+    /// # Example
+    /// ```
+    /// use anansi::{GlobalId, List, Workspace};
+    ///
+    /// let mut work = List::from_reader("Task 1\n".as_bytes()).unwrap();
+    /// let mut home = List::from_reader("".as_bytes()).unwrap();
+    /// work.set_history_depth(10);
+    /// home.set_history_depth(10);
+    ///
+    /// let mut workspace = Workspace::new();
+    /// workspace.insert("work", work);
+    /// workspace.insert("home", home);
+    /// workspace.move_task(&GlobalId::new("work", 1), "home").unwrap();
+    ///
+    /// assert!(workspace.undo_transfer());
+    /// assert_eq!(workspace.get("work").unwrap().task_amount(), 1);
+    /// assert_eq!(workspace.get("home").unwrap().task_amount(), 0);
+    /// assert!(!workspace.undo_transfer());
+    /// ```
+    pub fn undo_transfer(&mut self) -> bool {
+        let Some(names) = self.transfers.last() else {
+            return false;
+        };
+        let undoable = names
+            .iter()
+            .all(|name| self.lists.get(name).is_some_and(List::can_undo));
+        if !undoable {
+            return false;
+        }
+        for name in self.transfers.pop().unwrap_or_default() {
+            if let Some(list) = self.lists.get_mut(&name) {
+                list.undo();
+            }
+        }
+        true
+    }
+
+    /// Runs `f` on the two distinct lists named `from` and `to`.
+    fn transfer<F>(&mut self, from: &str, to: &str, f: F) -> AnansiResult<Vec<usize>>
+    where
+        F: FnOnce(&mut List, &mut List) -> AnansiResult<Vec<usize>>,
+    {
+        if !self.lists.contains_key(to) {
+            return Err(unknown_list(to));
+        }
+        let mut source = self.lists.remove(from).ok_or_else(|| unknown_list(from))?;
+        let result = f(&mut source, self.list_mut(to)?);
+        self.lists.insert(from.to_string(), source);
+        result
+    }
+
+    /// Saves all lists with unsaved changes.
+    ///
+    /// Returns the names of the saved lists. Stops at the first list that fails to save; the
+    /// lists saved before it stay saved.
+    pub fn save(&mut self) -> AnansiResult<Vec<String>> {
+        let mut saved = Vec::new();
+        for (name, list) in self.lists.iter_mut() {
+            if list.save_if_dirty()? {
+                saved.push(name.clone());
+            }
+        }
+        Ok(saved)
+    }
+
+    fn list_mut(&mut self, name: &str) -> AnansiResult<&mut List> {
+        self.lists.get_mut(name).ok_or_else(|| unknown_list(name))
+    }
+
+    fn collect_ids<F: Fn(&List) -> Vec<usize>>(&self, ids: F) -> Vec<GlobalId> {
+        self.lists
+            .iter()
+            .flat_map(|(name, list)| {
+                ids(list)
+                    .into_iter()
+                    .map(|id| GlobalId::new(name.clone(), id))
+            })
+            .collect()
+    }
+}
+
+fn unknown_task(id: &GlobalId) -> AnansiError {
+    AnansiError::InvalidID(format!("Task {} does not exist", id))
+}
+
+fn unknown_list(name: &str) -> AnansiError {
+    AnansiError::Generic(format!("No list named {} in the workspace", name))
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod workspaces {
+    use crate::{GlobalId, List, TransferOptions, Workspace};
+
+    #[test]
+    fn open_dir_and_save_dirty_lists() {
+        let dir = std::path::PathBuf::from("test-data/workspace-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("work.txt"), "Task 1 +report\nTask 2\n").unwrap();
+        std::fs::write(dir.join("home.txt"), "Task 3\n").unwrap();
+        std::fs::write(dir.join("done.txt"), "x Task 4 +report\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "not a list\n").unwrap();
+
+        let mut workspace = Workspace::open_dir(&dir).unwrap();
+        assert_eq!(workspace.names(), vec!["done", "home", "work"]);
+        assert_eq!(workspace.tasks().len(), 4);
+        assert_eq!(
+            workspace.by_project("report"),
+            vec![GlobalId::new("done", 1), GlobalId::new("work", 1)]
+        );
+
+        let moved = workspace
+            .move_task(&GlobalId::new("work", 2), "home")
+            .unwrap();
+        assert_eq!(workspace.task(&moved).unwrap().text(), "Task 2");
+        assert!(
+            workspace
+                .move_task(&GlobalId::new("work", 2), "home")
+                .is_err()
+        );
+        assert!(workspace.move_task(&moved, "nowhere").is_err());
+
+        assert_eq!(workspace.save().unwrap(), vec!["home", "work"]);
+        assert!(workspace.save().unwrap().is_empty());
+        assert_eq!(List::load(dir.join("home.txt")).unwrap().task_amount(), 2);
+        assert_eq!(List::load(dir.join("work.txt")).unwrap().task_amount(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_task_within_a_list() {
+        let mut workspace = Workspace::new();
+        workspace.insert("work", List::from_reader("Task 1\n".as_bytes()).unwrap());
+
+        let options = TransferOptions::new().with_provenance("work");
+        let copy = workspace
+            .copy_task(&GlobalId::new("work", 1), "work", &options)
+            .unwrap();
+        assert_eq!(copy, GlobalId::new("work", 2));
+        assert_eq!(workspace.task(&copy).unwrap().text(), "Task 1 from:work");
+        assert_eq!(workspace.get("work").unwrap().task_amount(), 2);
+        assert!(
+            workspace
+                .copy_task(&GlobalId::new("work", 7), "work", &options)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn transfers_are_undone_in_both_lists() {
+        let mut work = List::from_reader("Task 1\nTask 2\n".as_bytes()).unwrap();
+        let mut home = List::from_reader("Task 3\n".as_bytes()).unwrap();
+        work.set_history_depth(10);
+        home.set_history_depth(10);
+        let mut workspace = Workspace::new();
+        workspace.insert("work", work);
+        workspace.insert("home", home);
+        let amounts = |workspace: &Workspace| {
+            let amount = |name| workspace.get(name).unwrap().task_amount();
+            (amount("work"), amount("home"))
+        };
+
+        let options = TransferOptions::new();
+        workspace
+            .move_task(&GlobalId::new("work", 1), "home")
+            .unwrap();
+        workspace
+            .copy_task(&GlobalId::new("work", 2), "home", &options)
+            .unwrap();
+        assert_eq!(amounts(&workspace), (1, 3));
+
+        assert!(workspace.undo_transfer());
+        assert_eq!(amounts(&workspace), (1, 2));
+        assert!(workspace.undo_transfer());
+        assert_eq!(amounts(&workspace), (2, 1));
+        assert!(!workspace.undo_transfer());
+
+        // Without a history in the target list, nothing is undone
+        workspace.insert("inbox", List::from_reader("".as_bytes()).unwrap());
+        workspace
+            .move_task(&GlobalId::new("work", 1), "inbox")
+            .unwrap();
+        assert!(!workspace.undo_transfer());
+        assert_eq!(workspace.get("work").unwrap().task_amount(), 1);
+    }
+
+    #[test]
+    fn global_ids_parse() {
+        assert_eq!(
+            "a:b:12".parse::<GlobalId>().unwrap(),
+            GlobalId::new("a:b", 12)
+        );
+        assert!("work".parse::<GlobalId>().is_err());
+        assert!("work:x".parse::<GlobalId>().is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use std::path::PathBuf;
+
+/// Returns `test-data/<name>`, emptied of anything a previous run left behind.
+pub fn fresh_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from("test-data").join(name);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use anansi::*;
+
+mod common;
+use common::fresh_dir;
+
+#[test]
+fn concurrent_writers_do_not_interleave() {
+    let dir = fresh_dir("lock_concurrent");
+    let path = dir.join("todo.txt");
+
+    let writers: Vec<_> = (0..8)
+        .map(|n| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                List::with_locked(path, |list| {
+                    list.add(format!("Task {}", n));
+                })
+                .unwrap();
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    assert_eq!(List::load(&path).unwrap().task_amount(), 8);
+    assert!(!dir.join("todo.txt.lock").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn held_lock_times_out() {
+    let dir = fresh_dir("lock_held");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\n").unwrap();
+    // Held by this very process, so it is never considered abandoned
+    std::fs::write(
+        dir.join("todo.txt.lock"),
+        format!("{}\n{}\n0\n", std::process::id(), u64::MAX),
+    )
+    .unwrap();
+
+    let options = LockOptions::new().with_timeout(Duration::from_millis(50));
+    match List::load_locked(&path, options) {
+        Err(AnansiError::Locked(lock)) => assert_eq!(lock, dir.join("todo.txt.lock")),
+        other => panic!("expected the list to be locked, got {:?}", other),
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stale_lock_is_removed() {
+    let dir = fresh_dir("lock_stale");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\n").unwrap();
+    std::fs::write(
+        dir.join("todo.txt.lock"),
+        format!("{}\n0\n0\n", std::process::id()),
+    )
+    .unwrap();
+
+    let options = LockOptions::new().with_timeout(Duration::from_millis(50));
+    let mut list = List::load_locked(&path, options).unwrap();
+    list.add("Task 2");
+    list.save().unwrap();
+    assert_eq!(List::load(&path).unwrap().task_amount(), 2);
+    assert!(!dir.join("todo.txt.lock").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
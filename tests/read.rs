@@ -87,3 +87,47 @@ fn very_large_todo() {
     // Pushed down below 170ms after optimisation in release mode: ~45ms
     assert!(elapsed < 180_000);
 }
+
+#[test]
+fn from_reader_matches_load() {
+    for path in [
+        "test-data/basic-todo.txt",
+        "test-data/complex-todo.txt",
+        "test-data/leading-newline-todo.txt",
+        "test-data/trailing-newline-todo.txt",
+        "test-data/very-large-todo.txt",
+    ] {
+        let file = std::fs::File::open(path).unwrap();
+        let streamed = List::from_reader(std::io::BufReader::new(file)).unwrap();
+        let loaded = List::load(path).unwrap();
+        assert_eq!(streamed.tasks(), loaded.tasks(), "{}", path);
+        assert_eq!(streamed.open_task_amount(), loaded.open_task_amount());
+    }
+
+    let list = List::from_reader("\r\n\n Task 1\r\n\r\nx Task 2 \r\n\n".as_bytes()).unwrap();
+    assert_eq!(
+        list.tasks(),
+        List::from_reader("Task 1\n\nx Task 2".as_bytes())
+            .unwrap()
+            .tasks()
+    );
+    let list = List::from_reader(&b"\xEF\xBB\xBFTask 1\r\nTask \xff\r\n"[..]).unwrap();
+    let loaded = List::from_storage(MemoryStorage::with_contents(
+        &b"\xEF\xBB\xBFTask 1\r\nTask \xff\r\n"[..],
+    ))
+    .unwrap();
+    assert_eq!(list.tasks(), loaded.tasks());
+    assert_eq!(list.decode_issues(), loaded.decode_issues());
+    assert_eq!(list.line_ending(), LineEnding::CrLf);
+    assert!(list.has_bom());
+}
+
+#[test]
+fn write_to_round_trip() {
+    let list = List::load("test-data/complex-todo.txt").unwrap();
+    let mut output = Vec::new();
+    list.write_to(&mut output).unwrap();
+    let read = List::from_reader(output.as_slice()).unwrap();
+    let lines = |list: &List| list.tasks().iter().map(Task::to_string).collect::<Vec<_>>();
+    assert_eq!(lines(&read), lines(&list));
+}
@@ -0,0 +1,399 @@
+use anansi::*;
+
+mod common;
+use common::fresh_dir;
+
+#[test]
+fn atomic_save_leaves_no_temporary_files() {
+    let dir = fresh_dir("atomic_save");
+    let path = dir.join("todo.txt");
+
+    let mut list = List::new(&path);
+    list.add("Task 1");
+    assert!(list.save().is_ok());
+    list.add("Task 2");
+    assert!(list.save().is_ok());
+
+    let entries: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(entries, vec!["todo.txt".to_string()]);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Task 1\nTask 2\n");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn numbered_backups_rotate() {
+    let dir = fresh_dir("numbered_backups");
+    let path = dir.join("todo.txt");
+
+    let mut list = List::new(&path);
+    list.set_backup_policy(BackupPolicy::Numbered(2));
+    for n in 1..=4 {
+        list.add(format!("Task {}", n));
+        list.save().unwrap();
+    }
+
+    let backups = list.backups().unwrap();
+    assert_eq!(
+        backups,
+        vec![dir.join("todo.txt.bak.1"), dir.join("todo.txt.bak.2")]
+    );
+    assert_eq!(
+        std::fs::read_to_string(&backups[0]).unwrap(),
+        "Task 1\nTask 2\nTask 3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&backups[1]).unwrap(),
+        "Task 1\nTask 2\n"
+    );
+
+    list.restore_backup(1).unwrap();
+    assert_eq!(list.tasks().len(), 2);
+    assert_eq!(List::load(&path).unwrap().tasks().len(), 2);
+    assert!(list.restore_backup(5).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn timestamped_backups_are_pruned() {
+    let dir = fresh_dir("timestamped_backups");
+    let path = dir.join("todo.txt");
+
+    let mut list = List::new(&path);
+    list.set_backup_policy(BackupPolicy::Timestamped(2));
+    for n in 1..=4 {
+        list.add(format!("Task {}", n));
+        list.save().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let backups = list.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(
+        std::fs::read_to_string(&backups[0]).unwrap(),
+        "Task 1\nTask 2\nTask 3\n"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn external_changes_fail_by_default() {
+    let dir = fresh_dir("conflict_fail");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\nTask 2\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.add("Task 3");
+    std::fs::write(&path, "Task 1\nTask 2 +changed\n").unwrap();
+
+    assert!(list.has_external_changes().unwrap());
+    match list.save() {
+        Err(AnansiError::Conflict(conflict_path, lines)) => {
+            assert_eq!(conflict_path, path);
+            assert_eq!(lines, vec!["Task 2 +changed".to_string()]);
+        }
+        other => panic!("expected a conflict, got {:?}", other),
+    }
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Task 1\nTask 2 +changed\n"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn external_changes_are_merged() {
+    let dir = fresh_dir("conflict_merge");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\nTask 2\nTask 3\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.set_conflict_strategy(ConflictStrategy::Merge);
+    let first = list.tasks()[0].id();
+    let third = list.tasks()[2].id();
+    list.update_task(Task::new("Task 1 @home", first), first)
+        .unwrap();
+    list.remove(third);
+    list.add("Task 4");
+    std::fs::write(&path, "Task 0\nTask 1\nTask 2 +changed\nTask 3\n").unwrap();
+
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Task 0\nTask 1 @home\nTask 2 +changed\nTask 4\n"
+    );
+    assert_eq!(list.tasks().len(), 4);
+    assert!(!list.has_external_changes().unwrap());
+
+    // Both sides changed the same task
+    let second = list.by_text_to_vec("Task 2")[0].id();
+    list.update_task(Task::new("Task 2 +mine", second), second)
+        .unwrap();
+    std::fs::write(&path, "Task 0\nTask 1 @home\nTask 2 +theirs\nTask 4\n").unwrap();
+    match list.save() {
+        Err(AnansiError::Conflict(_, lines)) => {
+            assert_eq!(lines, vec!["Task 2 +mine".to_string()])
+        }
+        other => panic!("expected a conflict, got {:?}", other),
+    }
+
+    // Reloading lets the changes of the list win
+    list.set_conflict_strategy(ConflictStrategy::Reload);
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Task 0\nTask 1 @home\nTask 2 +mine\nTask 4\n"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn history_survives_restart() {
+    let dir = fresh_dir("history_restart");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\nTask 2\nTask 3\nTask 4\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.set_history_depth(10);
+    list.set_persist_history(true);
+    let ids: Vec<usize> = list.tasks().iter().map(|task| task.id()).collect();
+    list.remove(ids[0]);
+    list.begin_group();
+    for id in &ids[2..] {
+        list.remove(*id);
+    }
+    list.end_group();
+    list.add("Task 5");
+    list.save().unwrap();
+    drop(list);
+
+    let mut list = List::load(&path).unwrap();
+    list.set_history_depth(10);
+    assert!(list.load_history().unwrap());
+    assert!(list.undo());
+    assert!(list.undo());
+    let tasks = list.tasks();
+    let texts: Vec<&str> = tasks.iter().map(|task| task.text()).collect();
+    assert_eq!(texts, vec!["Task 2", "Task 3", "Task 4"]);
+    assert!(list.redo());
+    list.add("Task 6");
+    assert_eq!(list.task_amount(), 2);
+    list.save().unwrap();
+
+    // A history written for another version of the file is ignored
+    std::fs::write(&path, "Task 1\n").unwrap();
+    let mut list = List::load(&path).unwrap();
+    list.set_history_depth(10);
+    assert!(!list.load_history().unwrap());
+    assert!(!list.undo());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn storage_backed_lists_detect_conflicts() {
+    let storage = MemoryStorage::with_contents("Task 1\nTask 2\n");
+    let mut list = List::from_storage(storage.clone()).unwrap();
+    list.add("Task 3");
+
+    storage.set_contents("Task 1\nTask 2 +edited\n");
+    assert!(list.has_external_changes().unwrap());
+    assert!(matches!(list.save(), Err(AnansiError::Conflict(_, _))));
+
+    list.set_conflict_strategy(ConflictStrategy::Merge);
+    list.save().unwrap();
+    assert_eq!(
+        storage.contents().unwrap(),
+        b"Task 1\nTask 2 +edited\nTask 3\n"
+    );
+}
+
+#[test]
+fn windows_files_keep_their_format() {
+    let dir = fresh_dir("windows-format");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, b"\xEF\xBB\xBF(A) Task 1\r\nTask \xFF2\r\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    assert!(list.has_bom());
+    assert_eq!(list.line_ending(), LineEnding::CrLf);
+    assert_eq!(list.get(1).unwrap().prio(), Some('A'));
+    assert_eq!(list.decode_issues().len(), 1);
+    assert_eq!(list.decode_issues()[0].line(), 2);
+    assert_eq!(list.decode_issues()[0].bytes(), b"Task \xFF2");
+
+    list.add("Task 3");
+    list.save().unwrap();
+    assert!(list.decode_issues().is_empty());
+    assert!(!list.has_external_changes().unwrap());
+    assert_eq!(
+        std::fs::read(&path).unwrap(),
+        "\u{FEFF}(A) Task 1\r\nTask \u{FFFD}2\r\nTask 3\r\n".as_bytes()
+    );
+
+    list.set_line_ending(LineEnding::Lf);
+    list.set_bom(false);
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read(&path).unwrap(),
+        "(A) Task 1\nTask \u{FFFD}2\nTask 3\n".as_bytes()
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn done_tasks_are_archived() {
+    let dir = fresh_dir("archive");
+    let path = dir.join("todo.txt");
+    let done_path = dir.join("done.txt");
+    std::fs::write(&done_path, "x 2020-01-01 Old task +garden").unwrap();
+
+    let mut list = List::new(&path);
+    list.set_history_depth(10);
+    list.add("x Task 1 +garden");
+    list.add("Task 2 +garden");
+    list.add("x Task 3");
+    assert_eq!(list.archive(&done_path).unwrap(), 2);
+    assert_eq!(list.task_amount(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&done_path).unwrap(),
+        "x 2020-01-01 Old task +garden\nx Task 1 +garden\nx Task 3\n"
+    );
+    list.undo();
+    assert_eq!(list.done_task_amount(), 2);
+    list.redo();
+
+    list.add("x Task 4 +garden");
+    list.set_archive_path(Some(&done_path));
+    list.save().unwrap();
+    assert_eq!(List::load(&path).unwrap().done_task_amount(), 0);
+
+    let archive = Archive::load(&done_path).unwrap();
+    assert_eq!(archive.task_amount(), 4);
+    let everything = archive.include(&list);
+    assert_eq!(everything.by_project("garden").tasks().len(), 4);
+    assert_eq!(everything.open_task_amount(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn new_tasks_are_appended() {
+    let dir = fresh_dir("append");
+    let path = dir.join("todo.txt");
+    // Written as is, a full save would write "(A) Task 1"
+    std::fs::write(&path, "(A)  Task 1\r\nTask 2\r\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.add("Task 3");
+    list.save().unwrap();
+    assert!(!list.has_external_changes().unwrap());
+    list.add("Task 4");
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "(A)  Task 1\r\nTask 2\r\nTask 3\r\nTask 4\r\n"
+    );
+    assert_eq!(List::load(&path).unwrap().tasks(), list.tasks());
+
+    // Changes to saved tasks and to the file are written in full
+    list.add("Task 5");
+    list.update_task(Task::new("Task 2 +edited", 2), 2).unwrap();
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "(A) Task 1\r\nTask 2 +edited\r\nTask 3\r\nTask 4\r\nTask 5\r\n"
+    );
+    std::fs::write(&path, "Task 1\n").unwrap();
+    list.add("Task 6");
+    assert!(matches!(list.save(), Err(AnansiError::Conflict(_, _))));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn format_changes_are_written_in_full() {
+    let dir = fresh_dir("append_format");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\r\nTask 2\r\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.set_line_ending(LineEnding::Lf);
+    list.add("Task 3");
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Task 1\nTask 2\nTask 3\n"
+    );
+
+    list.set_bom(true);
+    list.add("Task 4");
+    list.save().unwrap();
+    assert_eq!(
+        std::fs::read(&path).unwrap(),
+        b"\xEF\xBB\xBFTask 1\nTask 2\nTask 3\nTask 4\n"
+    );
+
+    // Once written in the new format, tasks are appended again
+    list.add("Task 5");
+    list.save().unwrap();
+    assert!(!list.has_external_changes().unwrap());
+    assert_eq!(List::load(&path).unwrap().tasks(), list.tasks());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn restored_backups_are_written_in_full() {
+    let dir = fresh_dir("restore_append");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "A\nB\nC\n").unwrap();
+
+    let mut list = List::load(&path).unwrap();
+    list.set_backup_policy(BackupPolicy::Numbered(2));
+    list.update_task(Task::new("Z", 1), 1).unwrap();
+    list.remove(3usize);
+    list.save().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Z\nB\n");
+
+    // The restored list starts with the same ids as the file, but not the same lines
+    list.restore_backup(0).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "A\nB\nC\n");
+    assert_eq!(List::load(&path).unwrap().tasks(), list.tasks());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_restores_keep_the_list() {
+    let dir = fresh_dir("restore_conflict");
+    let path = dir.join("todo.txt");
+
+    let mut list = List::new(&path);
+    list.set_backup_policy(BackupPolicy::Numbered(2));
+    list.set_history_depth(10);
+    list.add("Task 1");
+    list.save().unwrap();
+    list.add("Task 2");
+    list.save().unwrap();
+
+    std::fs::write(&path, "Task 1\nTask 2\nTask 3\n").unwrap();
+    assert!(matches!(
+        list.restore_backup(0),
+        Err(AnansiError::Conflict(_, _))
+    ));
+    assert_eq!(list.task_amount(), 2);
+    assert!(list.can_undo());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Task 1\nTask 2\nTask 3\n"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,121 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::SystemTime,
+};
+
+use anansi::*;
+
+mod common;
+use common::fresh_dir;
+
+#[test]
+fn auto_reload_reports_changes() {
+    let dir = fresh_dir("watch_auto");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\nTask 2\nTask 3\n").unwrap();
+
+    let mut watched = WatchedList::new(List::load(&path).unwrap(), ReloadPolicy::Auto);
+    assert!(watched.poll().unwrap().is_none());
+
+    std::fs::write(&path, "Task 1\nx Task 2\nTask 4\nTask 5\n").unwrap();
+    let changes = watched.poll().unwrap().unwrap();
+    assert!(changes.is_reloaded());
+    assert_eq!(changes.modified().len(), 2);
+    assert_eq!(changes.modified()[0].0.text(), "Task 2");
+    assert!(changes.modified()[0].1.is_done());
+    assert_eq!(changes.modified()[1].1.text(), "Task 4");
+    assert_eq!(changes.added()[0].text(), "Task 5");
+    assert!(changes.removed().is_empty());
+    assert_eq!(watched.task_amount(), 4);
+    assert!(watched.poll().unwrap().is_none());
+
+    // Own saves are not reported
+    watched.add("Task 6");
+    watched.save().unwrap();
+    assert!(watched.poll().unwrap().is_none());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unsaved_changes_are_not_reloaded() {
+    let dir = fresh_dir("watch_notify");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\nTask 2\n").unwrap();
+
+    let mut watched = WatchedList::new(List::load(&path).unwrap(), ReloadPolicy::Auto);
+    watched.add("Task 3");
+    std::fs::write(&path, "Task 2\n").unwrap();
+
+    let changes = watched.poll().unwrap().unwrap();
+    assert!(!changes.is_reloaded());
+    assert_eq!(changes.removed()[0].text(), "Task 1");
+    assert_eq!(watched.task_amount(), 3);
+    // Reported only once
+    watched.set_compare_contents(true);
+    assert!(watched.poll().unwrap().is_none());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Counts how often the stored snapshot is read in full.
+#[derive(Debug)]
+struct CountingStorage {
+    inner: FileStorage,
+    reads: Arc<AtomicUsize>,
+}
+
+impl CountingStorage {
+    fn count(&self) {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Storage for CountingStorage {
+    fn read(&self) -> std::io::Result<Vec<u8>> {
+        self.count();
+        self.inner.read()
+    }
+
+    fn write(&self, contents: &[u8]) -> std::io::Result<()> {
+        self.inner.write(contents)
+    }
+
+    fn fingerprint(&self) -> std::io::Result<Option<Fingerprint>> {
+        self.count();
+        self.inner.fingerprint()
+    }
+
+    fn version(&self) -> std::io::Result<Option<(u64, Option<SystemTime>)>> {
+        self.inner.version()
+    }
+}
+
+#[test]
+fn polling_a_storage_does_not_read_it() {
+    let dir = fresh_dir("watch_storage");
+    let path = dir.join("todo.txt");
+    std::fs::write(&path, "Task 1\n").unwrap();
+    let reads = Arc::new(AtomicUsize::new(0));
+    let storage = CountingStorage {
+        inner: FileStorage::new(&path),
+        reads: reads.clone(),
+    };
+
+    let list = List::from_storage(storage).unwrap();
+    let mut watched = WatchedList::new(list, ReloadPolicy::Auto);
+    let loaded = reads.load(Ordering::Relaxed);
+    for _ in 0..3 {
+        assert!(watched.poll().unwrap().is_none());
+    }
+    assert_eq!(reads.load(Ordering::Relaxed), loaded);
+
+    std::fs::write(&path, "Task 1\nTask 2\n").unwrap();
+    assert!(watched.poll().unwrap().is_some());
+    assert_eq!(watched.task_amount(), 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}